@@ -0,0 +1,542 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::NtfyError;
+
+/// The maximum number of actions ntfy allows per notification.
+pub(crate) const MAX_ACTIONS: usize = 3;
+
+/// An action button attached to a notification, see [action buttons](https://docs.ntfy.sh/publish/#action-buttons).
+///
+/// Actions are serialized in ntfy's JSON format with the `serde` feature, and always with the
+/// `client` feature, which publishes and receives them as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    any(feature = "serde", feature = "client"),
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "action", rename_all = "lowercase")
+)]
+pub enum Action {
+    /// Opens a website or app when the action button is tapped.
+    View {
+        /// The label of the action button.
+        label: String,
+
+        /// The URL to open, e.g. `https://example.com` or `geo:0,0?q=1600+Amphitheatre+Parkway`.
+        url: String,
+
+        /// Clears the notification after the action button is tapped.
+        #[cfg_attr(any(feature = "serde", feature = "client"), serde(default))]
+        clear: bool,
+
+        /// Additional parameters not modeled by this crate, e.g. ones added by newer ntfy
+        /// versions, sent as `key=value` after the others. Not filled in for received actions.
+        #[cfg_attr(
+            any(feature = "serde", feature = "client"),
+            serde(default, flatten, skip_deserializing)
+        )]
+        extra: BTreeMap<String, String>,
+    },
+
+    /// Sends an HTTP request when the action button is tapped.
+    Http {
+        /// The label of the action button.
+        label: String,
+
+        /// The URL the request is sent to.
+        url: String,
+
+        /// The HTTP method of the request. ntfy defaults to `POST` if `None`.
+        #[cfg_attr(
+            any(feature = "serde", feature = "client"),
+            serde(skip_serializing_if = "Option::is_none")
+        )]
+        method: Option<String>,
+
+        /// Additional HTTP headers passed along with the request.
+        #[cfg_attr(
+            any(feature = "serde", feature = "client"),
+            serde(default, skip_serializing_if = "BTreeMap::is_empty")
+        )]
+        headers: BTreeMap<String, String>,
+
+        /// The body of the request.
+        #[cfg_attr(
+            any(feature = "serde", feature = "client"),
+            serde(skip_serializing_if = "Option::is_none")
+        )]
+        body: Option<String>,
+
+        /// Clears the notification after the action button is tapped.
+        #[cfg_attr(any(feature = "serde", feature = "client"), serde(default))]
+        clear: bool,
+
+        /// Additional parameters not modeled by this crate, e.g. ones added by newer ntfy
+        /// versions, sent as `key=value` after the others. Not filled in for received actions.
+        #[cfg_attr(
+            any(feature = "serde", feature = "client"),
+            serde(default, flatten, skip_deserializing)
+        )]
+        extra: BTreeMap<String, String>,
+    },
+
+    /// Sends an Android broadcast intent when the action button is tapped.
+    Broadcast {
+        /// The label of the action button.
+        label: String,
+
+        /// The Android intent name. ntfy defaults to `io.heckel.ntfy.USER_ACTION` if `None`.
+        #[cfg_attr(
+            any(feature = "serde", feature = "client"),
+            serde(skip_serializing_if = "Option::is_none")
+        )]
+        intent: Option<String>,
+
+        /// Extras passed along with the intent.
+        #[cfg_attr(
+            any(feature = "serde", feature = "client"),
+            serde(default, skip_serializing_if = "BTreeMap::is_empty")
+        )]
+        extras: BTreeMap<String, String>,
+
+        /// Clears the notification after the action button is tapped.
+        #[cfg_attr(any(feature = "serde", feature = "client"), serde(default))]
+        clear: bool,
+
+        /// Additional parameters not modeled by this crate, e.g. ones added by newer ntfy
+        /// versions, sent as `key=value` after the others. Not filled in for received actions.
+        #[cfg_attr(
+            any(feature = "serde", feature = "client"),
+            serde(default, flatten, skip_deserializing)
+        )]
+        extra: BTreeMap<String, String>,
+    },
+}
+
+impl Action {
+    /// Constructs an `HttpAction` builder for an `Http` action sending a `POST` request to `url`.
+    pub fn http(label: &str, url: &str) -> HttpAction {
+        HttpAction {
+            label: label.to_string(),
+            url: url.to_string(),
+            method: None,
+            headers: BTreeMap::new(),
+            body: None,
+            clear: false,
+        }
+    }
+
+    /// Constructs a `Broadcast` action with the given extras, e.g. from a `HashMap`.
+    pub fn broadcast<I, K, V>(label: &str, extras: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Action::Broadcast {
+            label: label.to_string(),
+            intent: None,
+            extras: extras
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+            clear: false,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Writes the action in ntfy's short `Actions` header format, e.g. `view, Open, https://example.com`.
+    fn serialize(&self) -> Result<String, NtfyError> {
+        let mut parts = Vec::new();
+        match self {
+            Action::View {
+                label,
+                url,
+                clear,
+                extra,
+            } => {
+                parts.push("view".to_string());
+                parts.push(quote(label)?);
+                parts.push(quote(url)?);
+                push_clear(&mut parts, *clear);
+                push_extra(&mut parts, extra)?;
+            }
+            Action::Http {
+                label,
+                url,
+                method,
+                headers,
+                body,
+                clear,
+                extra,
+            } => {
+                parts.push("http".to_string());
+                parts.push(quote(label)?);
+                parts.push(quote(url)?);
+                if let Some(method) = method {
+                    parts.push(format!("method={}", quote(method)?));
+                }
+                for (key, value) in headers {
+                    parts.push(format!("headers.{}={}", check_key(key)?, quote(value)?));
+                }
+                if let Some(body) = body {
+                    parts.push(format!("body={}", quote(body)?));
+                }
+                push_clear(&mut parts, *clear);
+                push_extra(&mut parts, extra)?;
+            }
+            Action::Broadcast {
+                label,
+                intent,
+                extras,
+                clear,
+                extra,
+            } => {
+                parts.push("broadcast".to_string());
+                parts.push(quote(label)?);
+                if let Some(intent) = intent {
+                    parts.push(format!("intent={}", quote(intent)?));
+                }
+                for (key, value) in extras {
+                    parts.push(format!("extras.{}={}", check_key(key)?, quote(value)?));
+                }
+                push_clear(&mut parts, *clear);
+                push_extra(&mut parts, extra)?;
+            }
+        }
+
+        Ok(parts.join(", "))
+    }
+}
+
+/// A builder for an `Http` action, see `Action::http`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpAction {
+    /// The label of the action button.
+    label: String,
+
+    /// The URL the request is sent to.
+    url: String,
+
+    /// The HTTP method of the request, `POST` if `None`.
+    method: Option<String>,
+
+    /// Additional HTTP headers passed along with the request.
+    headers: BTreeMap<String, String>,
+
+    /// The body of the request.
+    body: Option<String>,
+
+    /// Clears the notification after the action button is tapped.
+    clear: bool,
+}
+
+impl HttpAction {
+    /// Sets the HTTP method of the request, e.g. `reqwest::Method::PUT` or `"PUT"`. ntfy
+    /// defaults to `POST`.
+    pub fn method(self, method: impl AsRef<str>) -> Self {
+        Self {
+            method: Some(method.as_ref().to_string()),
+            ..self
+        }
+    }
+
+    /// Adds an HTTP header passed along with the request.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Sets the body of the request, e.g. a JSON document.
+    pub fn body(self, body: &str) -> Self {
+        Self {
+            body: Some(body.to_string()),
+            ..self
+        }
+    }
+
+    /// Clears the notification after the action button is tapped.
+    pub fn clear(self, clear: bool) -> Self {
+        Self { clear, ..self }
+    }
+}
+
+impl From<HttpAction> for Action {
+    fn from(action: HttpAction) -> Self {
+        Action::Http {
+            label: action.label,
+            url: action.url,
+            method: action.method,
+            headers: action.headers,
+            body: action.body,
+            clear: action.clear,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+fn push_clear(parts: &mut Vec<String>, clear: bool) {
+    if clear {
+        parts.push("clear=true".to_string());
+    }
+}
+
+fn push_extra(parts: &mut Vec<String>, extra: &BTreeMap<String, String>) -> Result<(), NtfyError> {
+    for (key, value) in extra {
+        parts.push(format!("{}={}", check_key(key)?, quote(value)?));
+    }
+
+    Ok(())
+}
+
+/// Rejects empty keys of headers and extras, or keys with characters that can't be quoted.
+fn check_key(key: &str) -> Result<&str, NtfyError> {
+    if key.is_empty()
+        || key.contains([',', ';', '=', '"', '\''])
+        || key.contains(char::is_whitespace)
+    {
+        return Err(NtfyError::InvalidInput(format!(
+            "action key `{key}` must be non-empty and must not contain whitespace or any of `,;=\"'`"
+        )));
+    }
+
+    Ok(key)
+}
+
+/// Quotes a value if it contains characters that have a meaning in the `Actions` header.
+fn quote(value: &str) -> Result<String, NtfyError> {
+    let needs_quotes = value.contains([',', ';', '=', '"', '\''])
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace);
+    if !needs_quotes {
+        return Ok(value.to_string());
+    }
+
+    match (value.contains('"'), value.contains('\'')) {
+        (false, _) => Ok(format!("\"{value}\"")),
+        (true, false) => Ok(format!("'{value}'")),
+        (true, true) => Err(NtfyError::InvalidInput(format!(
+            "action value `{value}` can't contain both single and double quotes"
+        ))),
+    }
+}
+
+/// Serializes up to three actions into the value of the `Actions` header, e.g.
+/// `view, Open, https://example.com; http, Close, https://api.example.com, method=PUT`.
+///
+/// Values containing `,`, `;`, `=` or quotes, or with leading or trailing whitespace, are
+/// quoted with double quotes, or single quotes if they contain double quotes. Values with both
+/// kinds of quotes and keys that would need quoting are rejected, as ntfy can't parse them.
+pub fn serialize_actions(actions: &[Action]) -> Result<String, NtfyError> {
+    if actions.len() > MAX_ACTIONS {
+        return Err(NtfyError::InvalidInput(format!(
+            "at most {MAX_ACTIONS} actions are allowed, got {}",
+            actions.len()
+        )));
+    }
+
+    let actions = actions
+        .iter()
+        .map(Action::serialize)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(actions.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_view_actions() {
+        let action = Action::View {
+            label: "Open portal".into(),
+            url: "https://home.nest.com/".into(),
+            clear: true,
+            extra: BTreeMap::new(),
+        };
+        assert_eq!(
+            serialize_actions(&[action]).unwrap(),
+            "view, Open portal, https://home.nest.com/, clear=true"
+        );
+    }
+
+    #[test]
+    fn serializes_http_actions() {
+        let action = Action::Http {
+            label: "Close door".into(),
+            url: "https://api.nest.com/".into(),
+            method: Some("PUT".into()),
+            headers: BTreeMap::from([("Authorization".into(), "Bearer zAzsx1sk..".into())]),
+            body: Some(r#"{"action": "close"}"#.into()),
+            clear: false,
+            extra: BTreeMap::new(),
+        };
+        assert_eq!(
+            serialize_actions(&[action]).unwrap(),
+            r#"http, Close door, https://api.nest.com/, method=PUT, headers.Authorization=Bearer zAzsx1sk.., body='{"action": "close"}'"#
+        );
+    }
+
+    #[test]
+    fn serializes_broadcast_actions() {
+        let action = Action::Broadcast {
+            label: "Take picture".into(),
+            intent: None,
+            extras: BTreeMap::from([
+                ("cmd".into(), "pic".into()),
+                ("camera".into(), "front".into()),
+            ]),
+            clear: false,
+            extra: BTreeMap::new(),
+        };
+        assert_eq!(
+            serialize_actions(&[action]).unwrap(),
+            "broadcast, Take picture, extras.camera=front, extras.cmd=pic"
+        );
+    }
+
+    #[test]
+    fn quotes_commas_and_semicolons() {
+        let view = |label: &str| Action::View {
+            label: label.into(),
+            url: "https://example.com".into(),
+            clear: false,
+            extra: BTreeMap::new(),
+        };
+        assert_eq!(
+            serialize_actions(&[view("Yes, really"), view("a;b")]).unwrap(),
+            r#"view, "Yes, really", https://example.com; view, "a;b", https://example.com"#
+        );
+        assert!(serialize_actions(&[view(r#"it's "quoted""#)]).is_err());
+    }
+
+    #[test]
+    fn rejects_more_than_three_actions() {
+        let view = Action::View {
+            label: "Open".into(),
+            url: "https://example.com".into(),
+            clear: false,
+            extra: BTreeMap::new(),
+        };
+        assert!(serialize_actions(&vec![view.clone(); 3]).is_ok());
+        assert!(matches!(
+            serialize_actions(&vec![view; 4]),
+            Err(NtfyError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn builds_broadcast_actions_from_maps() {
+        let extras = std::collections::HashMap::from([("cmd", "pic"), ("tags", "front,back")]);
+        let action = Action::broadcast("Take picture", extras);
+
+        assert_eq!(
+            serialize_actions(&[action]).unwrap(),
+            r#"broadcast, Take picture, extras.cmd=pic, extras.tags="front,back""#
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_extra_keys() {
+        for key in ["", "a=b", "a,b", "a b"] {
+            let action = Action::broadcast("Go", [(key, "value")]);
+            assert!(
+                matches!(
+                    serialize_actions(&[action]),
+                    Err(NtfyError::InvalidInput(_))
+                ),
+                "{key:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn builds_http_actions() {
+        let action: Action = Action::http("Close door", "https://api.example.com/door")
+            .method("PUT")
+            .header("X-Api-Key", "secret")
+            .body(r#"{"door":"closed"}"#)
+            .clear(true)
+            .into();
+
+        assert_eq!(
+            serialize_actions(&[action]).unwrap(),
+            r#"http, Close door, https://api.example.com/door, method=PUT, headers.X-Api-Key=secret, body='{"door":"closed"}', clear=true"#
+        );
+    }
+
+    #[test]
+    fn defaults_http_actions_to_post() {
+        let action: Action = Action::http("Ping", "https://example.com").into();
+        assert_eq!(
+            serialize_actions(&[action]).unwrap(),
+            "http, Ping, https://example.com"
+        );
+    }
+
+    #[test]
+    fn escapes_values_exhaustively() {
+        let view = |label: &str| Action::View {
+            label: label.into(),
+            url: "https://example.com".into(),
+            clear: false,
+            extra: BTreeMap::new(),
+        };
+        let label = |label: &str| {
+            let actions = serialize_actions(&[view(label)]).unwrap();
+            actions["view, ".len()..actions.len() - ", https://example.com".len()].to_string()
+        };
+
+        assert_eq!(label("plain"), "plain");
+        assert_eq!(label("a=b"), r#""a=b""#);
+        assert_eq!(label(" padded "), r#"" padded ""#);
+        assert_eq!(label(r#"say "hi""#), r#"'say "hi"'"#);
+        assert_eq!(label("it's"), r#""it's""#);
+        assert!(serialize_actions(&[view(r#"it's "both""#)]).is_err());
+    }
+
+    #[test]
+    fn appends_extra_parameters_verbatim() {
+        let action = Action::View {
+            label: "Open".into(),
+            url: "https://example.com".into(),
+            clear: true,
+            extra: BTreeMap::from([
+                ("icon".into(), "door".into()),
+                ("color".into(), "red, dark".into()),
+            ]),
+        };
+        assert_eq!(
+            serialize_actions(&[action]).unwrap(),
+            r#"view, Open, https://example.com, clear=true, color="red, dark", icon=door"#
+        );
+
+        let invalid = Action::View {
+            label: "Open".into(),
+            url: "https://example.com".into(),
+            clear: false,
+            extra: BTreeMap::from([("a b".into(), "c".into())]),
+        };
+        assert!(matches!(
+            serialize_actions(&[invalid]),
+            Err(NtfyError::InvalidInput(_))
+        ));
+    }
+
+    #[cfg(any(feature = "serde", feature = "client"))]
+    #[test]
+    fn flattens_extra_parameters_into_json() {
+        let action = Action::View {
+            label: "Open".into(),
+            url: "https://example.com".into(),
+            clear: false,
+            extra: BTreeMap::from([("icon".into(), "door".into())]),
+        };
+        let json = serde_json::to_value(&action).unwrap();
+
+        assert_eq!(json["icon"], "door");
+        assert_eq!(json["action"], "view");
+    }
+}
@@ -0,0 +1,307 @@
+//! A synchronous API for publishing notifications without an async runtime.
+
+use std::sync::{Arc, OnceLock};
+
+use reqwest::header::AUTHORIZATION;
+
+use crate::client::HttpConfig;
+use crate::{Client, Message, NtfyError, PublishResponse, Redirect, RequestContext};
+
+/// Sends a notifications and returns the message stored by the server, blocking the current
+/// thread until the request completes.
+///
+/// Behaves like the async [`ntfy`](crate::ntfy), except that notifications aren't retried.
+/// Must not be called from within an async runtime.
+pub fn ntfy(cli: &Client, msg: Message) -> Result<PublishResponse, NtfyError> {
+    let res = ntfy_raw(cli, msg)?;
+    let headers = res.headers().clone();
+    PublishResponse::from_parts(&headers, &res.text()?, 1)
+}
+
+/// Sends a notifications like [`ntfy`] but returns the raw response of the publish request.
+pub fn ntfy_raw(cli: &Client, mut msg: Message) -> Result<reqwest::blocking::Response, NtfyError> {
+    crate::publishing::apply_defaults(cli, &mut msg);
+    crate::publishing::check_size(cli, &msg)?;
+
+    let http_client = cli.blocking_client.get()?;
+    let url = crate::publishing::publish_url(cli, &msg)?;
+    let mut req = http_client.request(cli.publish_method.clone(), url);
+    if let Some(value) = crate::authorization(cli)? {
+        req = req.header(AUTHORIZATION, value);
+    }
+    for (name, value) in crate::publishing::checked_headers(cli, &msg)? {
+        req = req.header(crate::header::name(cli, &name), value);
+    }
+    if let Some(content_type) = &msg.content_type {
+        req = req.header(reqwest::header::CONTENT_TYPE, content_type);
+    }
+    if let Some(timeout) = msg.timeout {
+        req = req.timeout(timeout);
+    }
+
+    let (body, encoding) = crate::publishing::encode_body(cli, msg.message.into_bytes())?;
+    if let Some(encoding) = encoding {
+        req = req.header(reqwest::header::CONTENT_ENCODING, encoding);
+    }
+
+    let req = req.body(body).build()?;
+    let context = RequestContext::new(cli, req.method(), req.url());
+    if cli.dry_run {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            method = %req.method(),
+            url = %context.url,
+            headers = ?req.headers(),
+            "dry run, not sending request"
+        );
+        return Ok(crate::transport::dry_run_response(req.url())?.into());
+    }
+
+    let res = execute(cli, http_client, req).map_err(|e| e.with_context(&context))?;
+    if !res.status().is_success() {
+        if let Some(e) = NtfyError::rate_limited(res.status(), res.headers()) {
+            return Err(e);
+        }
+        let status = res.status().as_u16();
+        return Err(NtfyError::from_body(status, res.text()?).with_context(&context));
+    }
+
+    Ok(res)
+}
+
+/// Sends `req` and follows redirects according to the `Redirect` policy of `cli`, like the
+/// async transport.
+fn execute(
+    cli: &Client,
+    http_client: &reqwest::blocking::Client,
+    mut req: reqwest::blocking::Request,
+) -> Result<reqwest::blocking::Response, NtfyError> {
+    let mut followed = 0;
+    loop {
+        let resend = match cli.redirect {
+            Redirect::None => None,
+            _ => req.try_clone(),
+        };
+        let res = http_client.execute(req)?;
+        let Some(mut next) = resend else {
+            return Ok(res);
+        };
+        let from = next.url().clone();
+        let Some(target) =
+            cli.redirect
+                .target(next.method(), &from, res.status(), res.headers(), followed)
+        else {
+            return Ok(res);
+        };
+
+        crate::validate::secure(&target, cli.allow_insecure)?;
+        crate::redirect::strip_credentials(next.headers_mut(), &from, &target);
+        *next.url_mut() = target;
+        req = next;
+        followed += 1;
+    }
+}
+
+/// A blocking HTTP client which is built on first use and then shared between clones.
+///
+/// Building it lazily avoids constructing a blocking client inside an async runtime, which
+/// `reqwest` doesn't allow.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LazyClient {
+    /// The configuration applied when building the client.
+    config: HttpConfig,
+
+    /// The client once it has been built.
+    client: Arc<OnceLock<reqwest::blocking::Client>>,
+}
+
+impl LazyClient {
+    pub(crate) fn new(config: HttpConfig) -> Self {
+        Self {
+            config,
+            client: Arc::default(),
+        }
+    }
+
+    /// Returns the client, building it if this is the first use.
+    fn get(&self) -> Result<&reqwest::blocking::Client, NtfyError> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
+        }
+
+        let mut builder = reqwest::blocking::Client::builder();
+        // unlike the async client, the blocking one applies a 30s timeout unless told otherwise
+        builder = builder.timeout(self.config.timeout);
+        if let Some(timeout) = self.config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        for proxy in &self.config.proxies {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        builder = builder.redirect(reqwest::redirect::Policy::none());
+        builder = builder.user_agent(self.config.user_agent());
+        if let Some(max) = self.config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.config.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+        {
+            builder = builder.danger_accept_invalid_certs(self.config.accept_invalid_certs);
+            for cert in self.config.root_certificates()? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        let client = builder.build()?;
+        Ok(self.client.get_or_init(|| client))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_server::{response, TestServer, PUBLISHED};
+
+    #[test]
+    fn sends_notifications_synchronously() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(TestServer::start(vec![
+            response(200, &[], PUBLISHED),
+            response(403, &[], r#"{"code":40301,"http":403,"error":"forbidden"}"#),
+        ]));
+        let cli = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+
+        let msg = Message::builder("hi").title("Backup").build().unwrap();
+        ntfy(&cli, msg).unwrap();
+        let res = ntfy(&cli, Message::builder("again").build().unwrap());
+
+        assert!(matches!(
+            res,
+            Err(NtfyError::Server {
+                status: 403,
+                error: Some(crate::NtfyServerError { code: 40301, .. }),
+                ..
+            })
+        ));
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/test_uuid");
+        assert_eq!(requests[0].header("Title"), Some("Backup"));
+        assert_eq!(
+            requests[0].header("Authorization"),
+            Some("Bearer tk_secret")
+        );
+        assert_eq!(requests[0].body, b"hi");
+        assert_eq!(server.connections(), 1);
+    }
+
+    #[test]
+    fn rejects_filename_without_attachment() {
+        let cli = Client::new("http://127.0.0.1:9", "test", "uuid").unwrap();
+
+        let res = ntfy(
+            &cli,
+            Message::builder("hi").filename("a.txt").build_unchecked(),
+        );
+
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn overrides_the_content_type() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(TestServer::start(vec![response(200, &[], PUBLISHED)]));
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("a,b")
+            .content_type("text/csv")
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).unwrap();
+
+        assert_eq!(
+            server.requests()[0].header("Content-Type"),
+            Some("text/csv")
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_messages_before_sending() {
+        let cli = Client::new("http://127.0.0.1:9", "test", "uuid").unwrap();
+
+        let res = ntfy(&cli, Message::builder(&"x".repeat(4097)).build().unwrap());
+
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn applies_the_default_priority() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(TestServer::start(vec![response(200, &[], PUBLISHED)]));
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .default_priority(crate::Priority::High)
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap()).unwrap();
+
+        assert_eq!(server.requests()[0].header("Priority"), Some("4"));
+    }
+
+    #[test]
+    fn answers_dry_runs_without_sending() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(TestServer::start(vec![]));
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("backup")
+            .dry_run(true)
+            .build()
+            .unwrap();
+
+        let res = ntfy(&cli, Message::builder("done").build().unwrap()).unwrap();
+
+        assert_eq!(res.id, "dry-run");
+        assert_eq!(res.topic, "backup");
+        assert_eq!(server.connections(), 0);
+    }
+
+    #[test]
+    fn publishes_with_the_configured_method() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(TestServer::start(vec![response(200, &[], PUBLISHED)]));
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .publish_method(reqwest::Method::PUT)
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap()).unwrap();
+
+        assert_eq!(server.requests()[0].method, "PUT");
+    }
+
+    #[test]
+    fn resends_redirected_publishes() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(TestServer::start(vec![
+            response(308, &[("Location", "/moved/test_uuid")], ""),
+            response(200, &[], PUBLISHED),
+        ]));
+        let cli = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap()).unwrap();
+
+        let req = &server.requests()[1];
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/moved/test_uuid");
+        assert_eq!(req.body, b"hi");
+        assert_eq!(req.header("Authorization"), Some("Bearer tk_secret"));
+    }
+}
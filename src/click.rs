@@ -0,0 +1,120 @@
+//! Helpers for [click actions](https://docs.ntfy.sh/publish/#click-action), usable with
+//! `MessageBuilder::click`.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::NtfyError;
+
+/// Builds an Android `intent:` URI opening the app `package`, optionally with the intent
+/// `action` and string extras, e.g.
+/// `intent:#Intent;package=com.example.app;action=com.example.OPEN;S.id=42;end`.
+///
+/// Actions and extras are percent-encoded. Fails if `package` isn't a valid Android package
+/// name, i.e. at least two dot-separated segments of letters, digits or `_`, each starting with
+/// a letter, or if an extra key is empty or contains characters other than letters, digits,
+/// `.` or `_`.
+pub fn android_intent<I, K, V>(
+    package: &str,
+    action: Option<&str>,
+    extras: I,
+) -> Result<String, NtfyError>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    check_package(package)?;
+
+    let mut uri = format!("intent:#Intent;package={package}");
+    if let Some(action) = action {
+        uri.push_str(&format!(";action={}", encode(action)));
+    }
+    for (key, value) in extras {
+        let key = key.as_ref();
+        let valid = !key.is_empty()
+            && key
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'_');
+        if !valid {
+            return Err(NtfyError::InvalidInput(format!(
+                "intent extra `{key}` must consist of letters, digits, `.` or `_`"
+            )));
+        }
+        uri.push_str(&format!(";S.{key}={}", encode(value.as_ref())));
+    }
+    uri.push_str(";end");
+
+    Ok(uri)
+}
+
+/// Checks that `package` is a valid Android package name, e.g. `io.heckel.ntfy`.
+fn check_package(package: &str) -> Result<(), NtfyError> {
+    let segments: Vec<_> = package.split('.').collect();
+    let valid = segments.len() >= 2
+        && segments.iter().all(|segment| {
+            segment.starts_with(|c: char| c.is_ascii_alphabetic())
+                && segment
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        });
+    if !valid {
+        return Err(NtfyError::InvalidInput(format!(
+            "`{package}` is not a valid Android package name, expected e.g. `com.example.app`"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Percent-encodes everything but the characters Android's `Uri.encode` leaves unescaped.
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_intent_uris() {
+        let uri = android_intent(
+            "com.example.app",
+            Some("com.example.OPEN"),
+            [("id", "42"), ("note", "a b;c")],
+        )
+        .unwrap();
+        assert_eq!(
+            uri,
+            "intent:#Intent;package=com.example.app;action=com.example.OPEN;S.id=42;S.note=a%20b%3Bc;end"
+        );
+
+        let uri = android_intent("io.heckel.ntfy", None, [] as [(&str, &str); 0]).unwrap();
+        assert_eq!(uri, "intent:#Intent;package=io.heckel.ntfy;end");
+    }
+
+    #[test]
+    fn rejects_invalid_packages_and_extras() {
+        for package in ["", "app", "com..app", "com.1app", "com.exa-mple"] {
+            let res = android_intent(package, None, [] as [(&str, &str); 0]);
+            assert!(matches!(res, Err(NtfyError::InvalidInput(_))), "{package}");
+        }
+        let res = android_intent("com.example.app", None, [("a;b", "c")]);
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn produces_valid_click_urls() {
+        let uri = android_intent("com.example.app", None, [("id", "42")]).unwrap();
+        assert!(crate::Message::builder("hi").click(&uri).build().is_ok());
+    }
+}
@@ -0,0 +1,1263 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use crate::header;
+use crate::rate_limit::RateLimiter;
+use crate::transport::DryRun;
+use crate::{NtfyError, Priority, Proxy, Redirect, RetryPolicy, Topic, Transport};
+
+/// The maximum size of a message body accepted by ntfy.sh, see [limitations](https://docs.ntfy.sh/publish/#limitations).
+pub(crate) const DEFAULT_MAX_MESSAGE_BYTES: usize = 4096;
+
+/// The size limit of a single header line in common reverse proxies like nginx.
+pub(crate) const DEFAULT_MAX_HEADER_BYTES: usize = 8192;
+
+/// The `User-Agent` header sent unless another one is set with `ClientBuilder::user_agent`.
+pub(crate) const DEFAULT_USER_AGENT: &str = concat!("ntfy-rs/", env!("CARGO_PKG_VERSION"));
+
+/// Define the target for a notification.
+///
+/// Cloning a `Client` is cheap and shares the underlying HTTP connection pool.
+#[derive(Clone)]
+pub struct Client {
+    /// The URL of the ntfy server, e.g. `"https://ntfy.sh"`.
+    pub server: String,
+
+    /// The topic notifications are published to, e.g. `backup`, with an optional uuid suffix.
+    pub topic: Topic,
+
+    /// An optional [access token](https://docs.ntfy.sh/config/#access-tokens), e.g. `tk_...`, sent as
+    /// `Authorization: Bearer` header. Anonymous publishing is used if `None`.
+    pub token: Option<String>,
+
+    /// An optional username and password, sent as `Authorization: Basic` header.
+    ///
+    /// Can't be combined with `token`.
+    pub basic_auth: Option<(String, String)>,
+
+    /// The HTTP client used to send requests, built once and reused for every notification.
+    pub(crate) http_client: reqwest::Client,
+
+    /// The transport sending the requests, `http_client` unless replaced.
+    pub(crate) transport: Arc<dyn Transport>,
+
+    /// The policy for retrying failed notifications, which are not retried if `None`.
+    pub(crate) retry: Option<RetryPolicy>,
+
+    /// The maximum size of a message body in bytes, larger messages are rejected before sending.
+    pub(crate) max_message_bytes: usize,
+
+    /// The maximum combined size of the `Actions`, `Click` and `Attach` headers in bytes.
+    pub(crate) max_header_bytes: usize,
+
+    /// The API version path segment prefixed to all paths on the server, e.g. `v1`.
+    pub(crate) api_version: Option<String>,
+
+    /// Bodies larger than this many bytes are sent gzip compressed, never if `None`.
+    pub(crate) gzip_threshold: Option<usize>,
+
+    /// Whether ntfy headers are sent with an `X-` prefix, e.g. `X-Title`.
+    pub(crate) x_prefix: bool,
+
+    /// The priority of messages that don't set one themselves.
+    pub(crate) default_priority: Option<Priority>,
+
+    /// The tags of messages that don't set any themselves.
+    pub(crate) default_tags: Vec<String>,
+
+    /// Whether notifications are only built but never sent.
+    pub(crate) dry_run: bool,
+
+    /// Whether plain `http` URLs to hosts other than localhost are allowed.
+    pub(crate) allow_insecure: bool,
+
+    /// The HTTP method notifications are published with, `POST` or `PUT`.
+    pub(crate) publish_method: reqwest::Method,
+
+    /// How redirects of the server are followed, by resending the request.
+    pub(crate) redirect: Redirect,
+
+    /// Paces notifications, shared by all clones of the client.
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// Custom headers sent with every notification unless the message sets them itself.
+    pub(crate) default_headers: BTreeMap<String, String>,
+
+    /// The HTTP client used by the blocking API, built on first use.
+    #[cfg(feature = "blocking")]
+    pub(crate) blocking_client: crate::blocking::LazyClient,
+}
+
+impl Client {
+    /// Constructs a new `Client` via the `ClientBuilder` pattern.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Constructs a new `Client`.
+    ///
+    /// # Errors
+    ///
+    /// Fails like `ClientBuilder::build`, e.g. if the server isn't a valid URL or the topic or
+    /// uuid contain characters ntfy doesn't allow.
+    pub fn new(server: &str, topic: &str, uuid: &str) -> Result<Self, NtfyError> {
+        Self::builder()
+            .server(server)
+            .topic(topic)
+            .uuid(uuid)
+            .build()
+    }
+
+    /// Constructs a new `Client` from the environment variables `NTFY_SERVER` (defaults to
+    /// `"https://ntfy.sh"`), `NTFY_TOPIC`, `NTFY_UUID` and `NTFY_TOKEN`.
+    ///
+    /// Only `NTFY_TOPIC` is required, variables that are set to an empty string count as unset.
+    pub fn from_env() -> Result<Self, NtfyError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+        let missing: Vec<_> = ["NTFY_TOPIC"]
+            .into_iter()
+            .filter(|name| var(name).is_none())
+            .collect();
+        if !missing.is_empty() {
+            return Err(NtfyError::InvalidInput(format!(
+                "missing environment variables: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let mut builder = ClientBuilder::new();
+        if let Some(server) = var("NTFY_SERVER") {
+            builder = builder.server(&server);
+        }
+        if let Some(topic) = var("NTFY_TOPIC") {
+            builder = builder.topic(&topic);
+        }
+        if let Some(uuid) = var("NTFY_UUID") {
+            builder = builder.uuid(&uuid);
+        }
+        if let Some(token) = var("NTFY_TOKEN") {
+            builder = builder.token(&token);
+        }
+
+        builder.build()
+    }
+
+    /// Returns the URL notifications are published to, i.e. `server/topic_uuid` or `server/topic`
+    /// if the uuid is empty.
+    pub fn url(&self) -> Result<reqwest::Url, NtfyError> {
+        crate::topic_url(self, "")
+    }
+
+    /// Returns the configured credentials encoded for ntfy's `auth` query parameter, for
+    /// subscribers that can't set headers like a browser `EventSource`.
+    ///
+    /// Returns `None` if neither a token nor basic auth credentials are set.
+    pub fn auth_param(&self) -> Result<Option<String>, NtfyError> {
+        Ok(crate::authorization(self)?.map(|value| URL_SAFE_NO_PAD.encode(value.as_bytes())))
+    }
+
+    /// Constructs a new `Client` for a plain topic without a uuid suffix.
+    ///
+    /// # Errors
+    ///
+    /// Fails like `Client::new`.
+    pub fn new_topic(server: &str, topic: &str) -> Result<Self, NtfyError> {
+        Self::new(server, topic, "")
+    }
+
+    /// Constructs a new `Client` that authenticates with an access token.
+    ///
+    /// # Errors
+    ///
+    /// Fails like `Client::new`.
+    pub fn with_token(
+        server: &str,
+        topic: &str,
+        uuid: &str,
+        token: &str,
+    ) -> Result<Self, NtfyError> {
+        Self::builder()
+            .server(server)
+            .topic(topic)
+            .uuid(uuid)
+            .token(token)
+            .build()
+    }
+
+    /// Constructs a new `Client` that authenticates with a username and password.
+    ///
+    /// # Errors
+    ///
+    /// Fails like `Client::new`.
+    pub fn with_basic_auth(
+        server: &str,
+        topic: &str,
+        uuid: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, NtfyError> {
+        Self::builder()
+            .server(server)
+            .topic(topic)
+            .uuid(uuid)
+            .basic_auth(username, password)
+            .build()
+    }
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("server", &self.server)
+            .field("topic", &self.topic)
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .field(
+                "basic_auth",
+                &self.basic_auth.as_ref().map(|(user, _)| (user, "***")),
+            )
+            .field("http_client", &self.http_client)
+            .field("retry", &self.retry)
+            .field("max_message_bytes", &self.max_message_bytes)
+            .field("max_header_bytes", &self.max_header_bytes)
+            .field("api_version", &self.api_version)
+            .field("gzip_threshold", &self.gzip_threshold)
+            .field("x_prefix", &self.x_prefix)
+            .field("default_priority", &self.default_priority)
+            .field("default_tags", &self.default_tags)
+            .field("dry_run", &self.dry_run)
+            .field("allow_insecure", &self.allow_insecure)
+            .field("publish_method", &self.publish_method)
+            .field("redirect", &self.redirect)
+            .field("rate_limiter", &self.rate_limiter)
+            .field("default_headers", &self.default_headers.keys())
+            .finish()
+    }
+}
+
+pub struct ClientBuilder {
+    /// The URL of the ntfy server. Defaults to `"https://ntfy.sh"`.
+    server: String,
+
+    /// A topic which acts like a channel that receives notifications for that topic, e.g. `backup`.
+    topic: String,
+
+    /// A uuid (or another password like String) that will be appended to the topic for privacy reasons.
+    uuid: String,
+
+    /// An optional access token, sent as `Authorization: Bearer` header.
+    token: Option<String>,
+
+    /// An optional username and password, sent as `Authorization: Basic` header.
+    basic_auth: Option<(String, String)>,
+
+    /// The configuration of the underlying HTTP client.
+    http: HttpConfig,
+
+    /// The policy for retrying failed notifications, which are not retried if `None`.
+    retry: Option<RetryPolicy>,
+
+    /// A custom transport replacing the HTTP client built from `http`.
+    transport: Option<Arc<dyn Transport>>,
+
+    /// An HTTP client used instead of building one from `http`.
+    http_client: Option<reqwest::Client>,
+
+    /// The maximum size of a message body in bytes.
+    max_message_bytes: usize,
+
+    /// The maximum combined size of the `Actions`, `Click` and `Attach` headers in bytes.
+    max_header_bytes: usize,
+
+    /// The API version path segment prefixed to all paths on the server.
+    api_version: Option<String>,
+
+    /// Bodies larger than this many bytes are sent gzip compressed, never if `None`.
+    gzip_threshold: Option<usize>,
+
+    /// Whether ntfy headers are sent with an `X-` prefix.
+    x_prefix: bool,
+
+    /// The priority of messages that don't set one themselves.
+    default_priority: Option<Priority>,
+
+    /// The tags of messages that don't set any themselves.
+    default_tags: Vec<String>,
+
+    /// Whether notifications are only built but never sent.
+    dry_run: bool,
+
+    /// Whether plain `http` URLs to hosts other than localhost are allowed.
+    allow_insecure: bool,
+
+    /// The HTTP method notifications are published with.
+    publish_method: reqwest::Method,
+
+    /// The burst and replenish interval of the rate limiter, if any.
+    rate_limit: Option<(u32, Duration)>,
+
+    /// Custom headers sent with every notification.
+    default_headers: BTreeMap<String, String>,
+}
+
+/// The configuration applied when building the underlying HTTP clients.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpConfig {
+    /// The timeout applied to every request, from connecting until the response body has been
+    /// read. Requests never time out if `None`.
+    pub(crate) timeout: Option<Duration>,
+
+    /// The timeout for establishing a connection, which counts towards `timeout`. Connecting
+    /// never times out on its own if `None`.
+    pub(crate) connect_timeout: Option<Duration>,
+
+    /// The proxies requests are sent through.
+    pub(crate) proxies: Vec<Proxy>,
+
+    /// How redirects of the server are followed.
+    pub(crate) redirect: Redirect,
+
+    /// The `User-Agent` header sent with every request, `DEFAULT_USER_AGENT` if `None`.
+    pub(crate) user_agent: Option<String>,
+
+    /// The maximum number of idle connections kept per host, unlimited if `None`.
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+
+    /// How long idle connections are kept open, 90s if `None`.
+    pub(crate) pool_idle_timeout: Option<Duration>,
+
+    /// Disables the verification of TLS certificates.
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    pub(crate) accept_invalid_certs: bool,
+
+    /// Additional PEM encoded root certificates that are trusted.
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+}
+
+impl HttpConfig {
+    /// Builds the async HTTP client.
+    pub(crate) fn client(&self) -> Result<reqwest::Client, NtfyError> {
+        let mut http_client = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            http_client = http_client.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(timeout);
+        }
+        for proxy in &self.proxies {
+            http_client = http_client.proxy(proxy.to_reqwest()?);
+        }
+        // redirects are followed by `publishing::execute`, which resends the body
+        http_client = http_client.redirect(reqwest::redirect::Policy::none());
+        http_client = http_client.user_agent(self.user_agent());
+        if let Some(max) = self.pool_max_idle_per_host {
+            http_client = http_client.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            http_client = http_client.pool_idle_timeout(timeout);
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+        {
+            http_client = http_client.danger_accept_invalid_certs(self.accept_invalid_certs);
+            for cert in self.root_certificates()? {
+                http_client = http_client.add_root_certificate(cert);
+            }
+        }
+
+        Ok(http_client.build()?)
+    }
+
+    /// Returns the configured `User-Agent` or `DEFAULT_USER_AGENT`.
+    pub(crate) fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// Parses the additional root certificates.
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    pub(crate) fn root_certificates(&self) -> Result<Vec<reqwest::Certificate>, NtfyError> {
+        self.root_certificates
+            .iter()
+            .map(|pem| {
+                reqwest::Certificate::from_pem(pem)
+                    .map_err(|e| NtfyError::InvalidInput(format!("invalid root certificate: {e}")))
+            })
+            .collect()
+    }
+}
+
+impl ClientBuilder {
+    fn new() -> ClientBuilder {
+        ClientBuilder {
+            server: "https://ntfy.sh".to_string(),
+            topic: String::new(),
+            uuid: String::new(),
+            token: None,
+            basic_auth: None,
+            http: HttpConfig::default(),
+            retry: None,
+            transport: None,
+            http_client: None,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            max_header_bytes: DEFAULT_MAX_HEADER_BYTES,
+            api_version: None,
+            gzip_threshold: None,
+            x_prefix: false,
+            default_priority: None,
+            default_tags: Vec::new(),
+            dry_run: false,
+            allow_insecure: false,
+            publish_method: reqwest::Method::POST,
+            rate_limit: None,
+            default_headers: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the URL of the ntfy server, e.g. `"https://ntfy.example.com"`.
+    pub fn server(self, server: &str) -> Self {
+        Self {
+            server: server.to_string(),
+            ..self
+        }
+    }
+
+    /// Sets the topic notifications are published to, e.g. `"backup"`.
+    pub fn topic(self, topic: &str) -> Self {
+        Self {
+            topic: topic.to_string(),
+            ..self
+        }
+    }
+
+    /// Sets the uuid that will be appended to the topic. No suffix is appended if not set.
+    pub fn uuid(self, uuid: &str) -> Self {
+        Self {
+            uuid: uuid.to_string(),
+            ..self
+        }
+    }
+
+    /// Authenticates with an access token, e.g. `"tk_..."`.
+    pub fn token(self, token: &str) -> Self {
+        Self {
+            token: Some(token.to_string()),
+            ..self
+        }
+    }
+
+    /// Authenticates with a username and password.
+    pub fn basic_auth(self, username: &str, password: &str) -> Self {
+        Self {
+            basic_auth: Some((username.to_string(), password.to_string())),
+            ..self
+        }
+    }
+
+    /// Sets a timeout for every request, which can be overridden per message via
+    /// `MessageBuilder::timeout`.
+    ///
+    /// The timeout covers the whole request including its response body, so it also ends
+    /// subscriptions once it elapses.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a timeout for establishing the connection to the server, so requests fail fast with
+    /// `NtfyError::Timeout` if it's unreachable while slow responses and uploads can still use
+    /// the longer `timeout`.
+    ///
+    /// Connecting counts towards `timeout`, so a connect timeout longer than it has no effect.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends requests through a proxy, can be called multiple times to add e.g. separate proxies
+    /// for HTTP and HTTPS. Disables the proxies of the `HTTP_PROXY`, `HTTPS_PROXY` and
+    /// `ALL_PROXY` environment variables.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.http.proxies.push(proxy);
+        self
+    }
+
+    /// Sets how redirects of the server are followed, see `Redirect`. Follows up to 10 redirects
+    /// by default.
+    pub fn redirect(mut self, redirect: Redirect) -> Self {
+        self.http.redirect = redirect;
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept open per host. Unlimited by default.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.http.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets how long idle connections are kept open before they're closed. Defaults to 90s.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.http.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request. Defaults to `ntfy-rs/{version}`.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.http.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Disables the verification of TLS certificates, e.g. for a server with a self-signed
+    /// certificate.
+    ///
+    /// # Warning
+    ///
+    /// Any certificate is trusted, which makes the connection vulnerable to man-in-the-middle
+    /// attacks. Prefer adding the certificate via `add_root_certificate` instead.
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.http.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Trusts an additional PEM encoded root certificate, e.g. of a private CA. Can be called
+    /// multiple times.
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Self {
+        self.http.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Sets the maximum size of a message body in bytes, larger messages are rejected with
+    /// `NtfyError::InvalidInput` before sending. Defaults to ntfy.sh's limit of 4096 bytes,
+    /// self-hosted servers may allow more.
+    pub fn max_message_bytes(self, max_message_bytes: usize) -> Self {
+        Self {
+            max_message_bytes,
+            ..self
+        }
+    }
+
+    /// Sends requests with `http_client` instead of building an HTTP client.
+    ///
+    /// The timeout, proxy, pool, `User-Agent` and TLS settings of the builder are then ignored in
+    /// favor of the ones of `http_client`, though the blocking API still applies them. A custom
+    /// `transport` still takes precedence for sending.
+    ///
+    /// The `Redirect` policy of the builder only applies to redirects `http_client` doesn't
+    /// follow itself, so build it with `reqwest::redirect::Policy::none()` to keep publishes
+    /// from being turned into `GET` requests.
+    pub fn with_http_client(self, http_client: reqwest::Client) -> Self {
+        Self {
+            http_client: Some(http_client),
+            ..self
+        }
+    }
+
+    /// Sends all requests through `transport` instead of the network, e.g. a `MockTransport`
+    /// with the `test-util` feature.
+    pub fn transport(self, transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Some(Arc::new(transport)),
+            ..self
+        }
+    }
+
+    /// Sets the maximum combined size of the `Actions`, `Click` and `Attach` headers in bytes,
+    /// larger headers are rejected with `NtfyError::InvalidInput` before sending. Defaults to
+    /// 8192 bytes, the limit of common reverse proxies in front of ntfy.
+    pub fn max_header_bytes(self, max_header_bytes: usize) -> Self {
+        Self {
+            max_header_bytes,
+            ..self
+        }
+    }
+
+    /// Pins the API version path segment, e.g. `v1`, which is then prefixed to the paths of
+    /// topics, subscriptions and the JSON publish endpoint, e.g. `https://ntfy.sh/v1/mytopic`.
+    /// Unset by default, publishing to the unversioned paths ntfy currently serves. The health
+    /// check uses `v1` if unset.
+    pub fn api_version(self, version: &str) -> Self {
+        Self {
+            api_version: Some(version.trim_matches('/').to_string()),
+            ..self
+        }
+    }
+
+    /// Compresses bodies larger than `threshold` bytes with gzip and sends them with
+    /// `Content-Encoding: gzip`. Bodies are sent uncompressed by default, as not every proxy in
+    /// front of a self-hosted server may accept compressed requests.
+    pub fn gzip_threshold(self, threshold: usize) -> Self {
+        Self {
+            gzip_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    /// Sends all ntfy headers with an `X-` prefix, e.g. `X-Title` instead of `Title`, for proxies
+    /// that strip unknown headers without it.
+    pub fn use_x_prefix(self, x_prefix: bool) -> Self {
+        Self { x_prefix, ..self }
+    }
+
+    /// Sends messages without any tags with `tags`, tags set on the message itself replace them.
+    pub fn default_tags(self, tags: &[&str]) -> Self {
+        Self {
+            default_tags: crate::normalize_tags(tags),
+            ..self
+        }
+    }
+
+    /// Allows sending over plain `http` to hosts other than localhost, e.g. to an internal server.
+    ///
+    /// By default, such URLs are rejected with `NtfyError::InvalidInput`, as credentials and
+    /// messages would be sent unencrypted.
+    pub fn allow_insecure(self, allow_insecure: bool) -> Self {
+        Self {
+            allow_insecure,
+            ..self
+        }
+    }
+
+    /// Publishes notifications with `method` instead of `POST`, e.g. `PUT` for proxies that only
+    /// allow it. ntfy accepts both, other methods are rejected by `build`.
+    pub fn publish_method(self, method: reqwest::Method) -> Self {
+        Self {
+            publish_method: method,
+            ..self
+        }
+    }
+
+    /// Builds every request as usual but answers it with a synthetic success response instead
+    /// of sending it, e.g. to exercise notification code in CI without publishing anything.
+    ///
+    /// Takes precedence over a custom `transport`. With the `tracing` feature, the requests that
+    /// would have been sent are logged as debug events.
+    pub fn dry_run(self, dry_run: bool) -> Self {
+        Self { dry_run, ..self }
+    }
+
+    /// Sends messages without an explicit priority with `priority`, a priority set on the
+    /// message itself still takes precedence.
+    pub fn default_priority(self, priority: Priority) -> Self {
+        Self {
+            default_priority: Some(priority),
+            ..self
+        }
+    }
+
+    /// Adds a custom header sent with every notification, e.g. the key of an API gateway.
+    ///
+    /// A header of the same name set on the message with `MessageBuilder::header` takes
+    /// precedence. Headers managed by this crate, like `Title`, are rejected by `build`.
+    pub fn default_header(mut self, name: &str, value: &str) -> Self {
+        self.default_headers
+            .insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Paces notifications with a token bucket to stay below the rate limits of the server,
+    /// allowing `burst` notifications at once and replenishing one every `interval`.
+    ///
+    /// The limit is shared by all clones of the built client. Every attempt of a retried
+    /// notification counts against it. The blocking API isn't rate limited.
+    pub fn rate_limit(self, burst: u32, interval: Duration) -> Self {
+        Self {
+            rate_limit: Some((burst, interval)),
+            ..self
+        }
+    }
+
+    /// Retries notifications that failed for transient reasons according to `policy`.
+    pub fn retry(self, policy: RetryPolicy) -> Self {
+        Self {
+            retry: Some(policy),
+            ..self
+        }
+    }
+
+    /// Create a `Client` from a `ClientBuilder`.
+    ///
+    /// Fails if the server isn't a valid URL, the topic or uuid contain characters other than
+    /// `[-_A-Za-z0-9]` or are too long, both a token and basic auth credentials are set or a
+    /// root certificate can't be parsed.
+    pub fn build(self) -> Result<Client, NtfyError> {
+        let server = reqwest::Url::parse(&self.server)?;
+        crate::validate::secure(&server, self.allow_insecure)?;
+        if ![reqwest::Method::POST, reqwest::Method::PUT].contains(&self.publish_method) {
+            return Err(NtfyError::InvalidInput(format!(
+                "notifications can only be published with POST or PUT, not {}",
+                self.publish_method
+            )));
+        }
+        crate::validate::topic(&self.topic, &self.uuid)?;
+        if let Some(version) = &self.api_version {
+            crate::validate::api_version(version)?;
+        }
+        if self.token.is_some() && self.basic_auth.is_some() {
+            return Err(NtfyError::InvalidInput(
+                "token and basic auth are mutually exclusive".into(),
+            ));
+        }
+        if let Some(name) = self
+            .default_headers
+            .keys()
+            .find(|name| header::is_managed(name))
+        {
+            return Err(NtfyError::InvalidInput(format!(
+                "the `{name}` header is managed by ntfy-rs and can't be set manually"
+            )));
+        }
+
+        let http_client = match self.http_client {
+            Some(http_client) => http_client,
+            None => self.http.client()?,
+        };
+        Ok(Client {
+            server: self.server,
+            topic: Topic::with_suffix(&self.topic, &self.uuid),
+            token: self.token,
+            basic_auth: self.basic_auth,
+            transport: match self.transport {
+                _ if self.dry_run => Arc::new(DryRun),
+                Some(transport) => transport,
+                None => Arc::new(http_client.clone()),
+            },
+            http_client,
+            retry: self.retry,
+            max_message_bytes: self.max_message_bytes,
+            max_header_bytes: self.max_header_bytes,
+            api_version: self.api_version,
+            gzip_threshold: self.gzip_threshold,
+            x_prefix: self.x_prefix,
+            default_priority: self.default_priority,
+            default_tags: self.default_tags,
+            dry_run: self.dry_run,
+            allow_insecure: self.allow_insecure,
+            publish_method: self.publish_method,
+            redirect: self.http.redirect,
+            rate_limiter: self
+                .rate_limit
+                .map(|(burst, interval)| Arc::new(RateLimiter::new(burst, interval))),
+            default_headers: self.default_headers,
+            #[cfg(feature = "blocking")]
+            blocking_client: crate::blocking::LazyClient::new(self.http),
+        })
+    }
+}
+
+impl std::fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("server", &self.server)
+            .field("topic", &self.topic)
+            .field("uuid", &redact(&self.uuid))
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .field(
+                "basic_auth",
+                &self.basic_auth.as_ref().map(|(user, _)| (user, "***")),
+            )
+            .field("http", &self.http)
+            .field("http_client", &self.http_client)
+            .field("retry", &self.retry)
+            .field("max_message_bytes", &self.max_message_bytes)
+            .field("max_header_bytes", &self.max_header_bytes)
+            .field("api_version", &self.api_version)
+            .field("gzip_threshold", &self.gzip_threshold)
+            .field("x_prefix", &self.x_prefix)
+            .field("default_priority", &self.default_priority)
+            .field("default_tags", &self.default_tags)
+            .field("dry_run", &self.dry_run)
+            .field("allow_insecure", &self.allow_insecure)
+            .field("publish_method", &self.publish_method)
+            .field("rate_limit", &self.rate_limit)
+            .field("default_headers", &self.default_headers.keys())
+            .finish()
+    }
+}
+
+/// Masks a secret for `Debug` output, keeping an empty value recognizable.
+fn redact(secret: &str) -> &'static str {
+    if secret.is_empty() {
+        ""
+    } else {
+        "***"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Instant;
+
+    use crate::{ntfy, Message};
+
+    /// Returns the URL of a server that accepts connections but never responds.
+    async fn hanging_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn times_out_hanging_requests() {
+        let url = hanging_server().await;
+        let cli = Client::builder()
+            .server(&url)
+            .topic("test")
+            .uuid("uuid")
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap()).await;
+
+        assert!(matches!(res, Err(NtfyError::Timeout { .. })));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn overrides_the_timeout_per_message() {
+        let url = hanging_server().await;
+        let cli = Client::new(&url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi")
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let res = ntfy(&cli, msg).await;
+
+        assert!(matches!(res, Err(NtfyError::Timeout { .. })));
+    }
+
+    /// Returns the URL of a server whose accept queue is full, so connecting to it hangs.
+    ///
+    /// The listener and the queued connections must be kept alive while connecting.
+    async fn unreachable_server() -> (String, impl Sized) {
+        let socket = tokio::net::TcpSocket::new_v4().unwrap();
+        socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let listener = socket.listen(0).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut queued = Vec::new();
+        while let Ok(stream) =
+            std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(100))
+        {
+            queued.push(stream);
+        }
+        (format!("http://{addr}"), (listener, queued))
+    }
+
+    #[tokio::test]
+    async fn fails_fast_when_connecting_times_out() {
+        let (url, _server) = unreachable_server().await;
+        let cli = Client::builder()
+            .server(&url)
+            .topic("test")
+            .connect_timeout(Duration::from_millis(50))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap()).await;
+
+        assert!(matches!(res, Err(NtfyError::Timeout { .. })), "{res:?}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn keeps_the_request_timeout_for_slow_responses() {
+        let url = hanging_server().await;
+        let cli = Client::builder()
+            .server(&url)
+            .topic("test")
+            .connect_timeout(Duration::from_millis(20))
+            .timeout(Duration::from_millis(300))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap()).await;
+
+        assert!(matches!(res, Err(NtfyError::Timeout { .. })));
+        assert!(start.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn builds_fully_configured_clients() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("backup")
+            .uuid("1234")
+            .token("tk_secret")
+            .timeout(Duration::from_secs(10))
+            .user_agent("my-cli/1.0")
+            .retry(RetryPolicy::new(3))
+            .build()
+            .unwrap();
+
+        assert_eq!(cli.server, server.url);
+        assert_eq!(cli.topic, Topic::with_suffix("backup", "1234"));
+        assert_eq!(cli.token.as_deref(), Some("tk_secret"));
+        assert_eq!(cli.retry, Some(RetryPolicy::new(3)));
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.path, "/backup_1234");
+        assert_eq!(req.header("User-Agent"), Some("my-cli/1.0"));
+        assert_eq!(req.header("Authorization"), Some("Bearer tk_secret"));
+    }
+
+    #[test]
+    fn defaults_to_ntfy_sh() {
+        let cli = Client::builder().topic("backup").build().unwrap();
+
+        assert_eq!(cli.server, "https://ntfy.sh");
+    }
+
+    #[test]
+    fn rejects_invalid_configurations() {
+        let invalid_server = Client::builder().server("not a url").topic("t").build();
+        assert!(matches!(invalid_server, Err(NtfyError::Url(_))));
+
+        let empty_topic = Client::builder().build();
+        assert!(matches!(empty_topic, Err(NtfyError::InvalidInput(_))));
+
+        let both_auths = Client::builder()
+            .topic("t")
+            .token("tk_secret")
+            .basic_auth("user", "pass")
+            .build();
+        assert!(matches!(both_auths, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn redacts_credentials_in_builder_debug_output() {
+        let builder = Client::builder().token("tk_secret");
+
+        assert!(!format!("{builder:?}").contains("tk_secret"));
+    }
+
+    #[tokio::test]
+    async fn sends_the_default_user_agent() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let built = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .build()
+            .unwrap();
+        let shortcut = Client::new(&server.url, "test", "uuid").unwrap();
+
+        ntfy(&built, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        ntfy(&shortcut, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        let expected = format!("ntfy-rs/{}", env!("CARGO_PKG_VERSION"));
+        for req in server.requests() {
+            assert_eq!(req.header("User-Agent"), Some(expected.as_str()));
+        }
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    #[test]
+    fn applies_tls_options() {
+        let builder = Client::builder()
+            .topic("test")
+            .danger_accept_invalid_certs(true);
+        assert!(builder.http.accept_invalid_certs);
+        assert!(builder.build().is_ok());
+
+        let defaults = Client::builder().topic("test");
+        assert!(!defaults.http.accept_invalid_certs);
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    #[test]
+    fn rejects_invalid_root_certificates() {
+        let res = Client::builder()
+            .topic("test")
+            .add_root_certificate(
+                b"-----BEGIN CERTIFICATE-----\nnot base64\n-----END CERTIFICATE-----\n",
+            )
+            .build();
+
+        // depending on the backend, the certificate is parsed when adding or when building
+        assert!(res.is_err());
+    }
+
+    /// Serializes the tests modifying the `NTFY_*` environment variables.
+    static ENV: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn set_env(vars: &[(&str, &str)]) {
+        for name in ["NTFY_SERVER", "NTFY_TOPIC", "NTFY_UUID", "NTFY_TOKEN"] {
+            std::env::remove_var(name);
+        }
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+    }
+
+    #[test]
+    fn builds_clients_from_the_environment() {
+        let _guard = ENV.lock().unwrap();
+        set_env(&[
+            ("NTFY_SERVER", "https://ntfy.example.com"),
+            ("NTFY_TOPIC", "backup"),
+            ("NTFY_UUID", "1234"),
+            ("NTFY_TOKEN", "tk_secret"),
+        ]);
+
+        let cli = Client::from_env().unwrap();
+
+        assert_eq!(cli.server, "https://ntfy.example.com");
+        assert_eq!(cli.topic, Topic::with_suffix("backup", "1234"));
+        assert_eq!(cli.token.as_deref(), Some("tk_secret"));
+
+        set_env(&[("NTFY_TOPIC", "backup"), ("NTFY_SERVER", "")]);
+        let defaults = Client::from_env().unwrap();
+
+        assert_eq!(defaults.server, "https://ntfy.sh");
+        assert_eq!(defaults.topic, Topic::new("backup"));
+        assert_eq!(defaults.token, None);
+    }
+
+    #[test]
+    fn reports_a_missing_topic() {
+        let _guard = ENV.lock().unwrap();
+        set_env(&[("NTFY_SERVER", "https://ntfy.example.com")]);
+
+        match Client::from_env() {
+            Err(NtfyError::InvalidInput(msg)) => assert!(msg.contains("NTFY_TOPIC"), "{msg}"),
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn masks_the_uuid_in_debug_output() {
+        let cli = Client::builder()
+            .server("https://ntfy.sh")
+            .topic("backup")
+            .uuid("8fd2secret")
+            .basic_auth("user", "hunter2")
+            .build()
+            .unwrap();
+        let builder = Client::builder().uuid("8fd2secret");
+
+        for output in [format!("{cli:?}"), format!("{builder:?}")] {
+            assert!(!output.contains("8fd2secret"), "{output}");
+            assert!(!output.contains("hunter2"), "{output}");
+        }
+        assert!(format!("{builder:?}").contains(r#"uuid: "***""#));
+        assert!(format!("{cli:?}").contains(r#"server: "https://ntfy.sh""#));
+        assert!(format!("{cli:?}").contains(r#"name: "backup", suffix: Some("***")"#));
+        assert!(
+            format!("{:?}", Client::new("https://ntfy.sh", "t", "").unwrap())
+                .contains("suffix: None")
+        );
+    }
+
+    #[test]
+    fn encodes_credentials_as_auth_param() {
+        let cli = Client::builder()
+            .server("https://ntfy.sh")
+            .topic("test")
+            .basic_auth("testuser", "fakepassword")
+            .build()
+            .unwrap();
+
+        // the example from https://docs.ntfy.sh/subscribe/api/#query-param
+        assert_eq!(
+            cli.auth_param().unwrap().as_deref(),
+            Some("QmFzaWMgZEdWemRIVnpaWEk2Wm1GclpYQmhjM04zYjNKaw")
+        );
+        assert_eq!(
+            Client::new("https://ntfy.sh", "test", "")
+                .unwrap()
+                .auth_param()
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn returns_the_publish_url() {
+        let suffixed = Client::new("https://ntfy.sh/", "backup", "8fd2").unwrap();
+        let bare = Client::new_topic("https://ntfy.sh", "backup").unwrap();
+
+        assert_eq!(
+            suffixed.url().unwrap().as_str(),
+            "https://ntfy.sh/backup_8fd2"
+        );
+        assert_eq!(bare.url().unwrap().as_str(), "https://ntfy.sh/backup");
+        let mut invalid = bare;
+        invalid.server = "not a url".into();
+        assert!(invalid.url().is_err());
+    }
+
+    #[tokio::test]
+    async fn applies_pool_settings() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let builder = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .pool_max_idle_per_host(0)
+            .pool_idle_timeout(Duration::from_secs(5));
+        assert!(format!("{builder:?}").contains("pool_max_idle_per_host: Some(0)"));
+        assert!(format!("{builder:?}").contains("pool_idle_timeout: Some(5s)"));
+        let cli = builder.build().unwrap();
+
+        for _ in 0..2 {
+            ntfy(&cli, Message::builder("hi").build().unwrap())
+                .await
+                .unwrap();
+        }
+
+        // without idle connections, every request needs a new one
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(server.connections(), 2);
+    }
+
+    #[test]
+    fn validates_the_topic_in_constructors() {
+        assert!(Client::new("https://ntfy.sh", "backup", "8fd2").is_ok());
+        assert!(matches!(
+            Client::new("https://ntfy.sh", "my backup", ""),
+            Err(NtfyError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            Client::new_topic("https://ntfy.sh", &"a".repeat(65)),
+            Err(NtfyError::InvalidInput(_))
+        ));
+        assert!(Client::with_token("https://ntfy.sh", "backup", "bad uuid", "tk").is_err());
+        assert!(Client::builder()
+            .server("https://ntfy.sh")
+            .topic("a/b")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_managed_default_headers() {
+        for name in ["Title", "priority", "Authorization"] {
+            let res = Client::builder()
+                .topic("test")
+                .default_header(name, "x")
+                .build();
+            assert!(
+                matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains(&format!("`{name}`")))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_plain_http_to_public_hosts() {
+        let insecure = Client::builder()
+            .server("http://ntfy.sh")
+            .topic("t")
+            .build();
+        assert!(matches!(insecure, Err(NtfyError::InvalidInput(_))));
+
+        let local = Client::builder()
+            .server("http://localhost:8080")
+            .topic("t")
+            .build();
+        assert!(local.is_ok());
+
+        let allowed = Client::builder()
+            .server("http://ntfy.internal")
+            .topic("t")
+            .allow_insecure(true)
+            .build()
+            .unwrap();
+        assert!(allowed.allow_insecure);
+    }
+
+    #[test]
+    fn rejects_unsupported_publish_methods() {
+        let res = Client::builder()
+            .topic("t")
+            .publish_method(reqwest::Method::GET)
+            .build();
+
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn sends_with_the_provided_http_client() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let headers = reqwest::header::HeaderMap::from_iter([(
+            reqwest::header::HeaderName::from_static("x-tuned"),
+            reqwest::header::HeaderValue::from_static("yes"),
+        )]);
+        let http_client = reqwest::Client::builder()
+            .user_agent("tuned/1.0")
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .user_agent("ignored")
+            .with_http_client(http_client)
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.header("X-Tuned"), Some("yes"));
+        assert_eq!(req.header("User-Agent"), Some("tuned/1.0"));
+    }
+
+    #[test]
+    fn prefixes_paths_with_the_api_version() {
+        let cli = Client::builder()
+            .server("https://ntfy.sh/")
+            .topic("backup")
+            .api_version("/v1/")
+            .build()
+            .unwrap();
+
+        assert_eq!(cli.url().unwrap().as_str(), "https://ntfy.sh/v1/backup");
+        assert_eq!(
+            crate::api_url(&cli.server, &cli, "").unwrap().as_str(),
+            "https://ntfy.sh/v1/"
+        );
+
+        for version in ["", "v 1", "v1/beta"] {
+            let res = Client::builder().topic("t").api_version(version).build();
+            assert!(
+                matches!(res, Err(NtfyError::InvalidInput(_))),
+                "{version:?}"
+            );
+        }
+    }
+}
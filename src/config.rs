@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{Client, NtfyError, Priority};
+
+/// The settings of a `Client`, e.g. deserialized from the TOML or YAML config file of an app.
+///
+/// Only `topic` is required. Durations are written like `"30s"` or `"1m 30s"` and priorities
+/// like `"high"` or `4`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    /// The URL of the ntfy server, defaults to `https://ntfy.sh`.
+    pub server: Option<String>,
+
+    /// The name of the topic.
+    pub topic: String,
+
+    /// The uuid appended to the topic, if any.
+    pub uuid: Option<String>,
+
+    /// The access token to authenticate with, if any.
+    pub token: Option<String>,
+
+    /// The timeout of every request, see `ClientBuilder::timeout`.
+    #[serde(deserialize_with = "duration")]
+    pub timeout: Option<Duration>,
+
+    /// The timeout for connecting to the server, see `ClientBuilder::connect_timeout`.
+    #[serde(deserialize_with = "duration")]
+    pub connect_timeout: Option<Duration>,
+
+    /// The priority of messages that don't set one, see `ClientBuilder::default_priority`.
+    #[serde(deserialize_with = "priority")]
+    pub default_priority: Option<Priority>,
+
+    /// The tags of messages that don't set any, see `ClientBuilder::default_tags`.
+    pub default_tags: Vec<String>,
+}
+
+impl Client {
+    /// Constructs a new `Client` from `config`, validating it like `ClientBuilder::build`.
+    pub fn from_config(config: ClientConfig) -> Result<Self, NtfyError> {
+        let mut builder = Client::builder().topic(&config.topic);
+        if let Some(server) = &config.server {
+            builder = builder.server(server);
+        }
+        if let Some(uuid) = &config.uuid {
+            builder = builder.uuid(uuid);
+        }
+        if let Some(token) = &config.token {
+            builder = builder.token(token);
+        }
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(priority) = config.default_priority {
+            builder = builder.default_priority(priority);
+        }
+        let tags: Vec<_> = config.default_tags.iter().map(String::as_str).collect();
+
+        builder.default_tags(&tags).build()
+    }
+}
+
+fn duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|spec| humantime::parse_duration(&spec).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+fn priority<'de, D>(deserializer: D) -> Result<Option<Priority>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u8),
+        Name(String),
+    }
+
+    let priority = match Option::<Raw>::deserialize(deserializer)? {
+        Some(Raw::Number(value)) => Priority::try_from(value),
+        Some(Raw::Name(name)) => name.parse(),
+        None => return Ok(None),
+    };
+    priority.map(Some).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_from_toml() {
+        let config: ClientConfig = toml::from_str(
+            r#"
+            server = "https://ntfy.example.com"
+            topic = "backups"
+            uuid = "1234"
+            token = "tk_secret"
+            timeout = "1m 30s"
+            connect_timeout = "5s"
+            default_priority = "high"
+            default_tags = ["floppy_disk", " floppy_disk "]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.timeout, Some(Duration::from_secs(90)));
+        assert_eq!(config.connect_timeout, Some(Duration::from_secs(5)));
+        assert_eq!(config.default_priority, Some(Priority::High));
+
+        let cli = Client::from_config(config).unwrap();
+        assert_eq!(cli.server, "https://ntfy.example.com");
+        assert_eq!(cli.topic.path_segment(), "backups_1234");
+        assert_eq!(cli.token.as_deref(), Some("tk_secret"));
+        assert_eq!(cli.default_priority, Some(Priority::High));
+        assert_eq!(cli.default_tags, ["floppy_disk"]);
+    }
+
+    #[test]
+    fn accepts_numeric_priorities_and_defaults() {
+        let config: ClientConfig = toml::from_str("topic = \"t\"\ndefault_priority = 2").unwrap();
+
+        assert_eq!(config.default_priority, Some(Priority::Low));
+        assert_eq!(config.server, None);
+        assert_eq!(config.timeout, None);
+        assert_eq!(config.connect_timeout, None);
+        assert!(config.default_tags.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        assert!(toml::from_str::<ClientConfig>("topic = \"t\"\ntimeout = \"soon\"").is_err());
+        assert!(toml::from_str::<ClientConfig>("topic = \"t\"\ndefault_priority = 9").is_err());
+        assert!(toml::from_str::<ClientConfig>("topic = \"t\"\nretries = 3").is_err());
+        assert!(Client::from_config(ClientConfig::default()).is_err());
+    }
+}
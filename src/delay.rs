@@ -0,0 +1,260 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::NtfyError;
+
+/// The minimum delay ntfy allows for scheduled delivery.
+pub(crate) const MIN_DELAY: Duration = Duration::from_secs(10);
+
+/// The maximum delay ntfy allows for scheduled delivery.
+pub(crate) const MAX_DELAY: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// When a scheduled notification should be delivered, see [scheduled delivery](https://docs.ntfy.sh/publish/#scheduled-delivery).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Delay {
+    /// A duration like `"30min"` or a Unix timestamp, validated before sending, or one of the
+    /// natural-language forms ntfy understands like `"9am"` or `"tomorrow, 10am"`, which are
+    /// passed on as is and validated by the server.
+    Spec(String),
+
+    /// A duration after which the notification is delivered.
+    After(Duration),
+
+    /// A Unix timestamp in seconds.
+    At(i64),
+}
+
+impl Delay {
+    /// Validates the delay against ntfy's allowed window and returns the value of the `Delay` header.
+    pub(crate) fn header_value(&self) -> Result<String, NtfyError> {
+        match self {
+            Delay::Spec(spec) => {
+                let spec = spec.trim();
+                if let Ok(timestamp) = spec.parse::<i64>() {
+                    return Delay::At(timestamp).header_value();
+                }
+
+                if spec.is_empty() || spec.contains(char::is_control) {
+                    return Err(NtfyError::InvalidInput(format!(
+                        "delay {spec:?} must be non-empty and must not contain control characters"
+                    )));
+                }
+
+                // without `std`, durations are passed on unchecked like natural-language forms
+                #[cfg(feature = "std")]
+                if let Ok(duration) = humantime::parse_duration(spec) {
+                    check_window(duration)?;
+                    return Ok(format!("{}s", duration.as_secs()));
+                }
+
+                Ok(spec.to_string())
+            }
+            Delay::After(duration) => {
+                check_window(*duration)?;
+                Ok(format!("{}s", duration.as_secs()))
+            }
+            Delay::At(timestamp) => {
+                // without `std` there's no clock, so the server checks the window instead
+                #[cfg(feature = "std")]
+                {
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs() as i64);
+                    if *timestamp <= now {
+                        return Err(NtfyError::InvalidInput(format!(
+                            "delay timestamp {timestamp} lies in the past"
+                        )));
+                    }
+                    check_window(Duration::from_secs(timestamp.saturating_sub(now) as u64))?;
+                }
+
+                Ok(timestamp.to_string())
+            }
+        }
+    }
+}
+
+/// Checks that the delay is at least `MIN_DELAY` and at most `MAX_DELAY`.
+fn check_window(delay: Duration) -> Result<(), NtfyError> {
+    if delay < MIN_DELAY {
+        return Err(NtfyError::InvalidInput(format!(
+            "delay of {} is below the minimum of {}",
+            format_duration(delay),
+            format_duration(MIN_DELAY)
+        )));
+    }
+    if delay > MAX_DELAY {
+        return Err(NtfyError::InvalidInput(format!(
+            "delay of {} exceeds the maximum of {}",
+            format_duration(delay),
+            format_duration(MAX_DELAY)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Formats `duration` for error messages, e.g. `1h 30m`.
+fn format_duration(duration: Duration) -> String {
+    #[cfg(feature = "std")]
+    return humantime::format_duration(duration).to_string();
+    #[cfg(not(feature = "std"))]
+    return format!("{}s", duration.as_secs());
+}
+
+/// Converts a value into the delay of a scheduled notification, see `MessageBuilder::delay`.
+///
+/// Implemented for durations like `"30min"` or Unix timestamps as string, `std::time::Duration`,
+/// Unix timestamps as `i64` and, with the `std` feature, `chrono::Duration` and
+/// `chrono::DateTime`.
+pub trait IntoDelay {
+    /// Converts the value into a delay.
+    fn into_delay(self) -> Delay;
+}
+
+impl IntoDelay for &str {
+    fn into_delay(self) -> Delay {
+        Delay::Spec(self.to_string())
+    }
+}
+
+impl IntoDelay for String {
+    fn into_delay(self) -> Delay {
+        Delay::Spec(self)
+    }
+}
+
+impl IntoDelay for Duration {
+    fn into_delay(self) -> Delay {
+        Delay::After(self)
+    }
+}
+
+/// Negative durations are treated as zero and rejected.
+#[cfg(feature = "std")]
+impl IntoDelay for chrono::Duration {
+    fn into_delay(self) -> Delay {
+        Delay::After(self.to_std().unwrap_or_default())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Tz: chrono::TimeZone> IntoDelay for chrono::DateTime<Tz> {
+    fn into_delay(self) -> Delay {
+        Delay::At(self.timestamp())
+    }
+}
+
+/// A Unix timestamp in seconds.
+impl IntoDelay for i64 {
+    fn into_delay(self) -> Delay {
+        Delay::At(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+
+    fn now() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn normalizes_durations_to_seconds() {
+        assert_eq!(Delay::Spec("30min".into()).header_value().unwrap(), "1800s");
+        assert_eq!(Delay::Spec(" 2h ".into()).header_value().unwrap(), "7200s");
+    }
+
+    #[test]
+    fn accepts_timestamps_within_the_window() {
+        let at = now() + 3600;
+        assert_eq!(Delay::At(at).header_value().unwrap(), at.to_string());
+        assert_eq!(
+            Delay::Spec(at.to_string()).header_value().unwrap(),
+            at.to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn rejects_delays_outside_the_window() {
+        assert!(Delay::Spec("4days".into()).header_value().is_err());
+        assert!(Delay::At(now() + 4 * 24 * 3600).header_value().is_err());
+        assert!(Delay::Spec("5s".into()).header_value().is_err());
+        assert!(Delay::Spec("".into()).header_value().is_err());
+    }
+
+    #[test]
+    fn passes_natural_language_through() {
+        for spec in ["9am", "tomorrow, 10am"] {
+            assert_eq!(Delay::Spec(spec.into()).header_value().unwrap(), spec);
+        }
+        assert!(Delay::Spec("9am\r\nTitle: x".into())
+            .header_value()
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn rejects_past_and_zero_delays() {
+        for at in [now() - 60, now(), 0] {
+            assert!(
+                matches!(Delay::At(at).header_value(), Err(NtfyError::InvalidInput(e)) if e.contains("past")),
+                "{at}"
+            );
+        }
+        assert!(Delay::Spec("0s".into()).header_value().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn converts_each_delay_type() {
+        let at = now() + 3600;
+        let value = |delay: Delay| delay.header_value().unwrap();
+
+        assert_eq!(value("30min".into_delay()), "1800s");
+        assert_eq!(value(String::from("2h").into_delay()), "7200s");
+        assert_eq!(value(Duration::from_secs(90).into_delay()), "90s");
+        assert_eq!(value(chrono::Duration::minutes(5).into_delay()), "300s");
+        assert_eq!(value(at.into_delay()), at.to_string());
+
+        let datetime = chrono::DateTime::from_timestamp(at, 0).unwrap();
+        assert_eq!(value(datetime.into_delay()), at.to_string());
+        let local = datetime.with_timezone(&chrono::FixedOffset::west_opt(3600).unwrap());
+        assert_eq!(value(local.into_delay()), at.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn rejects_negative_chrono_durations() {
+        assert!(chrono::Duration::minutes(-5)
+            .into_delay()
+            .header_value()
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "std"))]
+    fn passes_durations_and_timestamps_through_without_std() {
+        assert_eq!(Delay::Spec("30min".into()).header_value().unwrap(), "30min");
+        assert_eq!(Delay::At(0).header_value().unwrap(), "0");
+        assert_eq!(
+            Delay::After(Duration::from_secs(90))
+                .header_value()
+                .unwrap(),
+            "90s"
+        );
+    }
+}
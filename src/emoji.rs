@@ -0,0 +1,39 @@
+//! Helpers for tags that are rendered as [emojis](https://docs.ntfy.sh/emojis/).
+
+/// Returns whether `code` is an emoji short code supported by ntfy, e.g. `warning` or
+/// `:warning:`.
+///
+/// Tags matching a short code are shown as emoji in front of the title instead of being listed
+/// below the notification.
+pub fn is_short_code(code: &str) -> bool {
+    let code = code
+        .strip_prefix(':')
+        .and_then(|code| code.strip_suffix(':'))
+        .unwrap_or(code);
+    emojis::get_by_shortcode(code).is_some()
+}
+
+/// Returns whether `tag` is wrapped in colons like a short code, e.g. `:warnign:`.
+pub(crate) fn looks_like_short_code(tag: &str) -> bool {
+    tag.len() > 2 && tag.starts_with(':') && tag.ends_with(':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_known_short_codes() {
+        assert!(is_short_code("warning"));
+        assert!(is_short_code(":warning:"));
+        assert!(!is_short_code(":warnign:"));
+        assert!(!is_short_code(""));
+    }
+
+    #[test]
+    fn recognizes_the_short_code_pattern() {
+        assert!(looks_like_short_code(":warnign:"));
+        assert!(!looks_like_short_code("warning"));
+        assert!(!looks_like_short_code("::"));
+    }
+}
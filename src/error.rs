@@ -0,0 +1,562 @@
+#[cfg(feature = "client")]
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "client")]
+use core::time::Duration;
+
+/// The error type returned by this crate.
+#[derive(Debug)]
+pub enum NtfyError {
+    /// The HTTP request could not be sent or its response could not be read.
+    #[cfg(feature = "client")]
+    Request {
+        /// The underlying error, without its URL as it may contain the uuid of the topic.
+        source: reqwest::Error,
+
+        /// The request that failed, if known.
+        context: Option<Box<RequestContext>>,
+    },
+
+    /// The connection to the server couldn't be established, e.g. because it's unreachable.
+    #[cfg(feature = "client")]
+    Connect {
+        /// The underlying error, without its URL as it may contain the uuid of the topic.
+        source: reqwest::Error,
+
+        /// The request that failed, if known.
+        context: Option<Box<RequestContext>>,
+    },
+
+    /// The TLS handshake with the server failed, e.g. because of an untrusted certificate.
+    ///
+    /// Detected on a best-effort basis from the error of the TLS backend.
+    #[cfg(feature = "client")]
+    Tls {
+        /// The underlying error, without its URL as it may contain the uuid of the topic.
+        source: reqwest::Error,
+
+        /// The request that failed, if known.
+        context: Option<Box<RequestContext>>,
+    },
+
+    /// The HTTP request didn't complete within the configured timeout.
+    #[cfg(feature = "client")]
+    Timeout {
+        /// The underlying error, without its URL as it may contain the uuid of the topic.
+        source: reqwest::Error,
+
+        /// The request that timed out, if known.
+        context: Option<Box<RequestContext>>,
+    },
+
+    /// The ntfy server responded with an unsuccessful status code.
+    #[cfg(feature = "client")]
+    Server {
+        /// The HTTP status code of the response.
+        status: u16,
+
+        /// The structured ntfy error, if the body could be parsed.
+        error: Option<NtfyServerError>,
+
+        /// The raw body of the response.
+        body: String,
+
+        /// The request the server responded to, if known.
+        context: Option<Box<RequestContext>>,
+    },
+
+    /// The server rejected the request with `429 Too Many Requests` and asked to retry after
+    /// the given duration via the `Retry-After` header.
+    ///
+    /// A `429` response without a valid `Retry-After` header is returned as `NtfyError::Server`.
+    #[cfg(feature = "client")]
+    RateLimited {
+        /// How long to wait before sending the next request.
+        retry_after: Duration,
+    },
+
+    /// The notification still failed after retrying it according to the `RetryPolicy`.
+    #[cfg(feature = "client")]
+    Retry {
+        /// The number of attempts made.
+        attempts: u32,
+
+        /// The error of the last attempt.
+        source: Box<NtfyError>,
+    },
+
+    /// An operation didn't complete within its timeout, e.g. a notification of
+    /// `ntfy_many_timeout` within its per-item timeout or a subscription that received nothing
+    /// within its keepalive timeout.
+    #[cfg(feature = "client")]
+    Elapsed {
+        /// The timeout that passed.
+        timeout: Duration,
+    },
+
+    /// A notification of `ntfy_many_timeout` wasn't done when the overall deadline passed.
+    #[cfg(feature = "client")]
+    Deadline {
+        /// The deadline that passed, relative to the start of the batch.
+        deadline: Duration,
+    },
+
+    /// A published message couldn't be polled from the server within the timeout of
+    /// `ntfy_confirmed`.
+    #[cfg(feature = "subscribe")]
+    Unconfirmed {
+        /// The id of the published message.
+        id: String,
+    },
+
+    /// The provided input was rejected before sending anything.
+    InvalidInput(String),
+
+    /// The target URL could not be parsed.
+    #[cfg(feature = "client")]
+    Url(url::ParseError),
+
+    /// A message received from the server could not be decoded.
+    #[cfg(feature = "client")]
+    Decode(serde_json::Error),
+
+    /// The WebSocket connection failed.
+    #[cfg(feature = "ws")]
+    WebSocket(tokio_tungstenite::tungstenite::Error),
+}
+
+/// The JSON error body ntfy returns for rejected requests, e.g.
+/// `{"code":41301,"http":413,"error":"limit reached: message too large","link":"..."}`.
+#[cfg(feature = "client")]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct NtfyServerError {
+    /// The ntfy specific error code, e.g. `40301`.
+    pub code: u32,
+
+    /// The HTTP status code.
+    pub http: u16,
+
+    /// The error message, e.g. `"forbidden"`.
+    pub error: String,
+
+    /// A link to the documentation explaining the error.
+    #[serde(default)]
+    pub link: Option<String>,
+}
+
+/// The request an error occurred for, with the uuid of the topic masked in the URL.
+#[cfg(feature = "client")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    /// The HTTP method, e.g. `POST`.
+    pub method: reqwest::Method,
+
+    /// The URL of the request, with the uuid suffix replaced by `***`.
+    pub url: String,
+
+    /// The name of the topic without its uuid suffix.
+    pub topic: String,
+}
+
+#[cfg(feature = "client")]
+impl RequestContext {
+    /// Captures the context of a request sent by `cli`.
+    pub(crate) fn new(cli: &crate::Client, method: &reqwest::Method, url: &reqwest::Url) -> Self {
+        Self {
+            method: method.clone(),
+            url: cli.topic.redact(url.as_str()),
+            topic: cli.topic.name().to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} (topic {})", self.method, self.url, self.topic)
+    }
+}
+
+impl fmt::Display for NtfyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "client")]
+        if let Some(context) = self.context() {
+            write!(f, "{context}: ")?;
+        }
+
+        match self {
+            #[cfg(feature = "client")]
+            NtfyError::Request { source, .. } => write!(f, "request failed: {source}"),
+            #[cfg(feature = "client")]
+            NtfyError::Connect { source, .. } => write!(f, "connection failed: {source}"),
+            #[cfg(feature = "client")]
+            NtfyError::Tls { source, .. } => write!(f, "tls handshake failed: {source}"),
+            #[cfg(feature = "client")]
+            NtfyError::Timeout { source, .. } => write!(f, "request timed out: {source}"),
+            #[cfg(feature = "client")]
+            NtfyError::Server {
+                status,
+                error: Some(error),
+                ..
+            } => write!(
+                f,
+                "server responded with {status} (code {}): {}",
+                error.code, error.error
+            ),
+            #[cfg(feature = "client")]
+            NtfyError::Server { status, body, .. } => {
+                write!(f, "server responded with {status}: {body}")
+            }
+            #[cfg(feature = "client")]
+            NtfyError::RateLimited { retry_after } => {
+                write!(f, "rate limited, retry after {}s", retry_after.as_secs())
+            }
+            #[cfg(feature = "client")]
+            NtfyError::Retry { attempts, source } => {
+                write!(f, "failed after {attempts} attempts: {source}")
+            }
+            #[cfg(feature = "client")]
+            NtfyError::Elapsed { timeout } => {
+                write!(f, "timed out after {timeout:?}")
+            }
+            #[cfg(feature = "client")]
+            NtfyError::Deadline { deadline } => {
+                write!(
+                    f,
+                    "batch deadline of {deadline:?} passed before the notification was done"
+                )
+            }
+            #[cfg(feature = "subscribe")]
+            NtfyError::Unconfirmed { id } => {
+                write!(f, "message {id} couldn't be confirmed in time")
+            }
+            NtfyError::InvalidInput(msg) => write!(f, "invalid input: {msg}"),
+            #[cfg(feature = "client")]
+            NtfyError::Url(e) => write!(f, "invalid url: {e}"),
+            #[cfg(feature = "client")]
+            NtfyError::Decode(e) => write!(f, "invalid message from server: {e}"),
+            #[cfg(feature = "ws")]
+            NtfyError::WebSocket(e) => write!(f, "websocket failed: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl NtfyError {
+    /// Returns the request the error occurred for, if known.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            NtfyError::Request { context, .. }
+            | NtfyError::Connect { context, .. }
+            | NtfyError::Tls { context, .. }
+            | NtfyError::Timeout { context, .. }
+            | NtfyError::Server { context, .. } => context.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Attaches `ctx` to request and server errors that don't have a context yet.
+    pub(crate) fn with_context(mut self, ctx: &RequestContext) -> Self {
+        if let NtfyError::Request { context, .. }
+        | NtfyError::Connect { context, .. }
+        | NtfyError::Tls { context, .. }
+        | NtfyError::Timeout { context, .. }
+        | NtfyError::Server { context, .. } = &mut self
+        {
+            context.get_or_insert_with(|| Box::new(ctx.clone()));
+        }
+        self
+    }
+
+    /// Builds a `NtfyError::Server` from an unsuccessful response, parsing the JSON error
+    /// body ntfy returns, e.g. `{"code":40301,"http":403,"error":"forbidden"}`.
+    pub(crate) async fn from_response(res: reqwest::Response) -> Self {
+        if let Some(e) = NtfyError::rate_limited(res.status(), res.headers()) {
+            return e;
+        }
+
+        let status = res.status().as_u16();
+        match res.text().await {
+            Ok(body) => NtfyError::from_body(status, body),
+            Err(e) => e.into(),
+        }
+    }
+
+    /// Builds a `NtfyError::RateLimited` if the status is `429` and the headers contain a valid
+    /// `Retry-After` header.
+    pub(crate) fn rate_limited(
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Option<Self> {
+        if status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return None;
+        }
+
+        let retry_after = crate::retry::retry_after(headers)?;
+        Some(NtfyError::RateLimited { retry_after })
+    }
+
+    /// Builds a `NtfyError::Server` from the status and body of an unsuccessful response.
+    pub(crate) fn from_body(status: u16, body: String) -> Self {
+        NtfyError::Server {
+            status,
+            error: serde_json::from_str(&body).ok(),
+            body,
+            context: None,
+        }
+    }
+}
+
+impl core::error::Error for NtfyError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "client")]
+            NtfyError::Request { source, .. } => Some(source),
+            #[cfg(feature = "client")]
+            NtfyError::Connect { source, .. } => Some(source),
+            #[cfg(feature = "client")]
+            NtfyError::Tls { source, .. } => Some(source),
+            #[cfg(feature = "client")]
+            NtfyError::Timeout { source, .. } => Some(source),
+            #[cfg(feature = "client")]
+            NtfyError::Retry { source, .. } => Some(source),
+            #[cfg(feature = "client")]
+            NtfyError::Url(e) => Some(e),
+            #[cfg(feature = "client")]
+            NtfyError::Decode(e) => Some(e),
+            #[cfg(feature = "ws")]
+            NtfyError::WebSocket(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl From<reqwest::Error> for NtfyError {
+    fn from(e: reqwest::Error) -> Self {
+        let source = e.without_url();
+        let context = None;
+        if source.is_timeout() {
+            NtfyError::Timeout { source, context }
+        } else if source.is_connect() && is_tls(&source) {
+            NtfyError::Tls { source, context }
+        } else if source.is_connect() {
+            NtfyError::Connect { source, context }
+        } else {
+            NtfyError::Request { source, context }
+        }
+    }
+}
+
+/// Returns whether any error in the source chain of `e` stems from the TLS backend, as reqwest
+/// doesn't classify TLS errors itself.
+#[cfg(feature = "client")]
+fn is_tls(e: &reqwest::Error) -> bool {
+    let mut source = core::error::Error::source(e);
+    while let Some(e) = source {
+        if is_tls_backend_error(e) {
+            return true;
+        }
+        // hyper wraps the errors of the TLS backends in `io::Error`, whose `source` skips them
+        source = match e.downcast_ref::<std::io::Error>().and_then(|e| e.get_ref()) {
+            Some(wrapped) => Some(wrapped as &(dyn core::error::Error + 'static)),
+            None => e.source(),
+        };
+    }
+
+    false
+}
+
+/// Returns whether `e` is an error of an enabled TLS backend.
+///
+/// Errors of other types are only recognized by their message mentioning TLS or certificates,
+/// as a fallback for backend errors converted into other error types along the way.
+#[cfg(feature = "client")]
+fn is_tls_backend_error(e: &(dyn core::error::Error + 'static)) -> bool {
+    #[cfg(feature = "rustls-tls")]
+    if e.is::<rustls::Error>() {
+        return true;
+    }
+    #[cfg(feature = "native-tls")]
+    if e.is::<native_tls::Error>() {
+        return true;
+    }
+
+    let text = e.to_string().to_ascii_lowercase();
+    ["certificate", "tls", "ssl", "handshake"]
+        .iter()
+        .any(|word| text.contains(word))
+}
+
+#[cfg(feature = "client")]
+impl From<url::ParseError> for NtfyError {
+    fn from(e: url::ParseError) -> Self {
+        NtfyError::Url(e)
+    }
+}
+
+#[cfg(feature = "client")]
+impl From<serde_json::Error> for NtfyError {
+    fn from(e: serde_json::Error) -> Self {
+        NtfyError::Decode(e)
+    }
+}
+
+#[cfg(feature = "ws")]
+impl From<tokio_tungstenite::tungstenite::Error> for NtfyError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        NtfyError::WebSocket(e)
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn displays_the_failure() {
+        let e = NtfyError::Server {
+            status: 502,
+            error: None,
+            body: "bad gateway".into(),
+            context: None,
+        };
+        assert_eq!(e.to_string(), "server responded with 502: bad gateway");
+        assert_eq!(
+            NtfyError::InvalidInput("empty topic".into()).to_string(),
+            "invalid input: empty topic"
+        );
+    }
+
+    #[test]
+    fn converts_url_errors() {
+        let e: NtfyError = url::Url::parse("not a url").unwrap_err().into();
+        assert!(matches!(e, NtfyError::Url(_)));
+        assert!(e.source().is_some());
+    }
+
+    #[tokio::test]
+    async fn ntfy_rejects_invalid_server_urls() {
+        assert!(matches!(
+            crate::Client::new("not a url", "test", "uuid"),
+            Err(NtfyError::Url(_))
+        ));
+
+        // the fields are public, so they're validated again when sending
+        let mut cli = crate::Client::new("https://ntfy.sh", "test", "uuid").unwrap();
+        cli.server = "not a url".into();
+        let res = crate::ntfy(&cli, crate::Message::builder("hi").build().unwrap()).await;
+        assert!(matches!(res, Err(NtfyError::Url(_))));
+    }
+
+    #[tokio::test]
+    async fn surfaces_rate_limits() {
+        use crate::test_server::{response, TestServer};
+
+        let server = TestServer::start(vec![
+            response(429, &[("Retry-After", "30")], "slow down"),
+            response(429, &[], "slow down"),
+        ])
+        .await;
+        let cli = crate::Client::new(&server.url, "test", "uuid").unwrap();
+
+        let limited = crate::ntfy(&cli, crate::Message::builder("hi").build().unwrap()).await;
+        let unknown = crate::ntfy(&cli, crate::Message::builder("hi").build().unwrap()).await;
+
+        assert!(matches!(
+            limited,
+            Err(NtfyError::RateLimited { retry_after }) if retry_after == Duration::from_secs(30)
+        ));
+        assert!(matches!(
+            unknown,
+            Err(NtfyError::Server { status: 429, .. })
+        ));
+    }
+
+    #[test]
+    fn parses_structured_error_bodies() {
+        let body = r#"{"code":41301,"http":413,"error":"limit reached: message too large","link":"https://ntfy.sh/docs/publish/#limitations"}"#;
+        let e = NtfyError::from_body(413, body.into());
+
+        assert!(matches!(
+            &e,
+            NtfyError::Server {
+                status: 413,
+                error: Some(NtfyServerError {
+                    code: 41301,
+                    http: 413,
+                    link: Some(link),
+                    ..
+                }),
+                ..
+            } if link == "https://ntfy.sh/docs/publish/#limitations"
+        ));
+        assert_eq!(
+            e.to_string(),
+            "server responded with 413 (code 41301): limit reached: message too large"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_plain_text_bodies() {
+        let e = NtfyError::from_body(502, "<html>bad gateway</html>".into());
+
+        assert!(matches!(
+            &e,
+            NtfyError::Server {
+                status: 502,
+                error: None,
+                body,
+                ..
+            } if body == "<html>bad gateway</html>"
+        ));
+        assert_eq!(
+            e.to_string(),
+            "server responded with 502: <html>bad gateway</html>"
+        );
+    }
+
+    #[tokio::test]
+    async fn classifies_refused_connections() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let e = NtfyError::from(reqwest::get(url).await.unwrap_err());
+
+        assert!(matches!(e, NtfyError::Connect { .. }), "{e:?}");
+        assert!(e.to_string().starts_with("connection failed: "));
+    }
+
+    #[tokio::test]
+    async fn classifies_failed_tls_handshakes() {
+        use tokio::io::AsyncWriteExt;
+
+        // a plain HTTP server answering the TLS client hello
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("https://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let _ = stream
+                    .write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let e = NtfyError::from(reqwest::get(url).await.unwrap_err());
+
+        assert!(matches!(e, NtfyError::Tls { .. }), "{e:?}");
+        assert!(e.to_string().starts_with("tls handshake failed: "));
+    }
+
+    #[test]
+    fn recognizes_tls_errors_by_message_as_fallback() {
+        let e = std::io::Error::other("invalid peer certificate: UnknownIssuer");
+        assert!(is_tls_backend_error(&e));
+        let e = std::io::Error::other("connection refused");
+        assert!(!is_tls_backend_error(&e));
+    }
+}
@@ -0,0 +1,292 @@
+use std::time::Duration;
+
+use crate::{validate, Client, MessageId, NtfyError, Priority, PublishResponse, Since};
+
+/// Server-side filters for subscribing and polling, see [filter messages](https://docs.ntfy.sh/subscribe/api/#filter-messages).
+///
+/// Only messages matching all configured filters are returned, an empty filter matches everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscribeFilter {
+    /// Further topics subscribed to in addition to the topic of the `Client`.
+    topics: Vec<String>,
+
+    /// Only return cached messages published after this point.
+    since: Option<Since>,
+
+    /// Only return messages with one of these priorities (1-5).
+    priority: Vec<u8>,
+
+    /// Only return messages with all of these tags.
+    tags: Vec<String>,
+
+    /// Only return messages with exactly this title.
+    title: Option<String>,
+
+    /// Only return messages with exactly this body text.
+    message: Option<String>,
+
+    /// Only return the message with this id.
+    id: Option<MessageId>,
+
+    /// Subscriptions are considered dead if nothing arrives within this duration.
+    pub(crate) keepalive_timeout: Option<Duration>,
+}
+
+impl SubscribeFilter {
+    /// Constructs a new `SubscribeFilter` that matches all messages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also subscribes to the given topics, merging their messages into a single stream. The
+    /// `topic` field of each message tells which topic it was published to.
+    ///
+    /// The topics are used as is, so any uuid suffix must already be part of them, e.g.
+    /// `"backup_8fd2..."`.
+    pub fn topics(self, topics: &[&str]) -> Self {
+        Self {
+            topics: topics.iter().map(|topic| topic.to_string()).collect(),
+            ..self
+        }
+    }
+
+    /// Also returns cached messages published after `since`.
+    pub fn since(self, since: Since) -> Self {
+        Self {
+            since: Some(since),
+            ..self
+        }
+    }
+
+    /// Sets `since` unless it's already set.
+    pub(crate) fn since_or(self, since: Since) -> Self {
+        Self {
+            since: Some(self.since.unwrap_or(since)),
+            ..self
+        }
+    }
+
+    /// Only returns messages with one of the given priorities, e.g. `&[4, 5]`.
+    ///
+    /// Replaces the priorities set via `min_priority` and vice versa, the last call wins.
+    pub fn priority(self, priority: &[u8]) -> Self {
+        Self {
+            priority: priority.to_vec(),
+            ..self
+        }
+    }
+
+    /// Only returns messages with at least the given priority, e.g. `Priority::High` returns
+    /// messages with priority 4 and 5.
+    ///
+    /// Replaces the priorities set via `priority` and vice versa, the last call wins.
+    pub fn min_priority(self, priority: Priority) -> Self {
+        Self {
+            priority: (priority.as_u8()..=Priority::Max.as_u8()).collect(),
+            ..self
+        }
+    }
+
+    /// Only returns messages with all of the given tags.
+    pub fn tags(self, tags: &[&str]) -> Self {
+        Self {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..self
+        }
+    }
+
+    /// Only returns messages with exactly the given title.
+    pub fn title(self, title: &str) -> Self {
+        Self {
+            title: Some(title.to_string()),
+            ..self
+        }
+    }
+
+    /// Only returns messages with exactly the given body text.
+    pub fn message(self, message: &str) -> Self {
+        Self {
+            message: Some(message.to_string()),
+            ..self
+        }
+    }
+
+    /// Only returns the message with the given id, e.g. `PublishResponse::message_id`.
+    pub fn id(self, id: impl Into<MessageId>) -> Self {
+        Self {
+            id: Some(id.into()),
+            ..self
+        }
+    }
+
+    /// Only returns the message published with `res`, e.g. to check that it has been cached.
+    pub fn published(self, res: &PublishResponse) -> Self {
+        self.id(res.message_id()).since(Since::All)
+    }
+
+    /// Considers a subscription dead if neither a message nor a `keepalive` event arrives within
+    /// `timeout`, ending it with `NtfyError::Elapsed`. `subscribe_reconnecting` then reconnects.
+    ///
+    /// ntfy sends keepalives every 45s by default, so a timeout of e.g. 2 minutes detects
+    /// silently dropped connections. Subscriptions never time out by default.
+    pub fn keepalive_timeout(self, timeout: Duration) -> Self {
+        Self {
+            keepalive_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Builds the URL of `endpoint`, e.g. `/json`, for the topic of `cli` and all further
+    /// topics, with the filter appended as query parameters.
+    pub(crate) fn url(&self, cli: &Client, endpoint: &str) -> Result<reqwest::Url, NtfyError> {
+        cli.topic.validate()?;
+        for topic in &self.topics {
+            validate::topic(topic, "")?;
+        }
+
+        let mut topics = vec![cli.topic.path_segment()];
+        topics.extend(self.topics.iter().cloned());
+        let mut url = crate::api_url(&cli.server, cli, &format!("{}{endpoint}", topics.join(",")))?;
+        self.apply(&mut url)?;
+
+        Ok(url)
+    }
+
+    /// Appends the filter as query parameters to `url`.
+    fn apply(&self, url: &mut reqwest::Url) -> Result<(), NtfyError> {
+        if let Some(p) = self.priority.iter().find(|p| !(1..=5).contains(*p)) {
+            return Err(NtfyError::InvalidInput(format!(
+                "priority filter must be between 1 and 5, got {p}"
+            )));
+        }
+
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(since) = &self.since {
+                query.append_pair("since", &since.query_value());
+            }
+            if !self.priority.is_empty() {
+                let priority: Vec<_> = self.priority.iter().map(u8::to_string).collect();
+                query.append_pair("priority", &priority.join(","));
+            }
+            if !self.tags.is_empty() {
+                query.append_pair("tags", &self.tags.join(","));
+            }
+            if let Some(title) = &self.title {
+                query.append_pair("title", title);
+            }
+            if let Some(message) = &self.message {
+                query.append_pair("message", message);
+            }
+            if let Some(id) = &self.id {
+                query.append_pair("id", id.as_str());
+            }
+        }
+        // an empty filter must not leave a dangling `?` behind
+        if url.query() == Some("") {
+            url.set_query(None);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_combined_filters() {
+        let filter = SubscribeFilter::new()
+            .since(Since::All)
+            .priority(&[4, 5])
+            .tags(&["alert", "disk"])
+            .title("Disk full")
+            .message("95% used")
+            .id("abc");
+        let mut url = reqwest::Url::parse("https://ntfy.sh/test/json?poll=1").unwrap();
+        filter.apply(&mut url).unwrap();
+
+        assert_eq!(
+            url.query(),
+            Some("poll=1&since=all&priority=4%2C5&tags=alert%2Cdisk&title=Disk+full&message=95%25+used&id=abc")
+        );
+    }
+
+    #[test]
+    fn leaves_urls_untouched_without_filters() {
+        let mut url = reqwest::Url::parse("https://ntfy.sh/test/sse").unwrap();
+        SubscribeFilter::new().apply(&mut url).unwrap();
+
+        assert_eq!(url.as_str(), "https://ntfy.sh/test/sse");
+    }
+
+    #[test]
+    fn rejects_out_of_range_priorities() {
+        for priority in [0, 6] {
+            let mut url = reqwest::Url::parse("https://ntfy.sh/test/sse").unwrap();
+            let res = SubscribeFilter::new()
+                .priority(&[4, priority])
+                .apply(&mut url);
+            assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+        }
+    }
+
+    #[test]
+    fn joins_further_topics() {
+        let cli = Client::new("https://ntfy.sh/", "alerts", "").unwrap();
+        let filter = SubscribeFilter::new()
+            .topics(&["backup_8fd2", "deploys"])
+            .since(Since::All);
+        let url = filter.url(&cli, "/json").unwrap();
+
+        assert_eq!(
+            url.as_str(),
+            "https://ntfy.sh/alerts,backup_8fd2,deploys/json?since=all"
+        );
+    }
+
+    #[test]
+    fn keeps_the_uuid_suffix_of_the_client_topic() {
+        let cli = Client::new("https://ntfy.sh", "alerts", "8fd2").unwrap();
+        let url = SubscribeFilter::new()
+            .topics(&["deploys"])
+            .url(&cli, "/sse")
+            .unwrap();
+
+        assert_eq!(url.as_str(), "https://ntfy.sh/alerts_8fd2,deploys/sse");
+    }
+
+    #[test]
+    fn rejects_invalid_topics() {
+        let cli = Client::new("https://ntfy.sh", "alerts", "").unwrap();
+        for topic in ["", "a,b", "a/b"] {
+            let res = SubscribeFilter::new().topics(&[topic]).url(&cli, "/json");
+            assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+        }
+    }
+
+    #[test]
+    fn expands_min_priority() {
+        let mut url = reqwest::Url::parse("https://ntfy.sh/test/json").unwrap();
+        SubscribeFilter::new()
+            .min_priority(Priority::High)
+            .apply(&mut url)
+            .unwrap();
+
+        assert_eq!(url.query(), Some("priority=4%2C5"));
+    }
+
+    #[test]
+    fn last_priority_filter_wins() {
+        let filter = SubscribeFilter::new()
+            .priority(&[1])
+            .min_priority(Priority::Max);
+        assert_eq!(filter, SubscribeFilter::new().priority(&[5]));
+
+        let filter = SubscribeFilter::new()
+            .min_priority(Priority::Min)
+            .priority(&[3]);
+        assert_eq!(filter, SubscribeFilter::new().priority(&[3]));
+    }
+}
@@ -0,0 +1,199 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+#[cfg(feature = "client")]
+use crate::Client;
+
+/// The maximum number of raw bytes encoded into a single RFC 2047 encoded-word, chosen so that
+/// each word stays below the 75 character limit of the RFC.
+const MAX_WORD_BYTES: usize = 45;
+
+/// Encodes a header value so it can be safely sent over HTTP.
+///
+/// Pure-ASCII values are returned untouched, anything else is emitted as one or more
+/// [RFC 2047](https://datatracker.ietf.org/doc/html/rfc2047) Base64 encoded-words
+/// (`=?UTF-8?B?...?=`), which the ntfy server decodes.
+pub(crate) fn encode_value(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+
+    let mut words = Vec::new();
+    let mut start = 0;
+    for (idx, ch) in value.char_indices() {
+        if idx + ch.len_utf8() - start > MAX_WORD_BYTES {
+            words.push(encode_word(&value[start..idx]));
+            start = idx;
+        }
+    }
+    words.push(encode_word(&value[start..]));
+
+    words.join(" ")
+}
+
+fn encode_word(chunk: &str) -> String {
+    format!("=?UTF-8?B?{}?=", STANDARD.encode(chunk))
+}
+
+/// The headers set by this crate itself including all aliases ntfy accepts for them, which can't
+/// be overridden by custom headers.
+const MANAGED: &[&str] = &[
+    "Message",
+    "m",
+    "Title",
+    "t",
+    "ti",
+    "Priority",
+    "prio",
+    "p",
+    "Tags",
+    "Tag",
+    "ta",
+    "Delay",
+    "d",
+    "At",
+    "In",
+    "Actions",
+    "Action",
+    "x",
+    "Click",
+    "Attach",
+    "a",
+    "Filename",
+    "File",
+    "f",
+    "Icon",
+    "Email",
+    "E-Mail",
+    "Mail",
+    "e",
+    "Call",
+    "Markdown",
+    "md",
+    "Template",
+    "tpl",
+    "Cache",
+    "Firebase",
+    "Authorization",
+    "Content-Type",
+    "Content-Encoding",
+];
+
+/// Returns the name to send the ntfy header `name` as, prefixed with `X-` if enabled for `cli`.
+///
+/// Custom headers and standard HTTP headers like `Authorization` are never prefixed.
+#[cfg(feature = "client")]
+pub(crate) fn name(cli: &Client, name: &str) -> String {
+    let standard = ["Authorization", "Content-Type", "Content-Encoding"]
+        .iter()
+        .any(|standard| standard.eq_ignore_ascii_case(name));
+    if cli.x_prefix && !standard && is_managed(name) {
+        format!("X-{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Returns whether `name` is a header managed by this crate, including its `X-` prefixed alias.
+pub(crate) fn is_managed(name: &str) -> bool {
+    let name = name.trim();
+    let unprefixed = match name.get(..2) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("x-") => &name[2..],
+        _ => name,
+    };
+    MANAGED
+        .iter()
+        .any(|managed| managed.eq_ignore_ascii_case(unprefixed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the encoded-words of `value` like the ntfy server does.
+    fn decode(value: &str) -> String {
+        let bytes: Vec<u8> = value
+            .split(' ')
+            .flat_map(|word| {
+                let encoded = word
+                    .strip_prefix("=?UTF-8?B?")
+                    .and_then(|word| word.strip_suffix("?="))
+                    .unwrap();
+                STANDARD.decode(encoded).unwrap()
+            })
+            .collect();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn keeps_ascii_untouched() {
+        assert_eq!(encode_value("Backup done"), "Backup done");
+    }
+
+    #[test]
+    fn round_trips_unicode() {
+        let encoded = encode_value("Café backup ✅");
+        assert!(encoded.is_ascii());
+        assert!(encoded.starts_with("=?UTF-8?B?"));
+        assert_eq!(decode(&encoded), "Café backup ✅");
+    }
+
+    #[test]
+    fn splits_long_values_into_short_words_at_char_boundaries() {
+        let title = "Ünïcödé ".repeat(20);
+        let encoded = encode_value(&title);
+        assert!(encoded.split(' ').count() > 1);
+        assert!(encoded.split(' ').all(|word| word.len() <= 75));
+        assert_eq!(decode(&encoded), title);
+    }
+
+    #[test]
+    fn detects_managed_headers_and_aliases() {
+        for name in [
+            "Title", "title", "X-Title", "x-TITLE", "t", "ti", "prio", "ta", "X-Tag",
+        ] {
+            assert!(is_managed(name), "{name}");
+        }
+        for name in [
+            "d",
+            "delay",
+            "x",
+            "a",
+            "f",
+            "file",
+            "e",
+            "mail",
+            "X-E-Mail",
+            "icon",
+            "content-type",
+        ] {
+            assert!(is_managed(name), "{name}");
+        }
+        for name in ["X-Custom", "Accept", "UnifiedPush", "X-"] {
+            assert!(!is_managed(name), "{name}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "client")]
+    fn prefixes_managed_header_names_if_enabled() {
+        let cli = Client::new("https://ntfy.sh", "test", "uuid").unwrap();
+        assert_eq!(name(&cli, "Title"), "Title");
+
+        let cli = Client::builder()
+            .server("https://ntfy.sh")
+            .topic("test")
+            .uuid("uuid")
+            .use_x_prefix(true)
+            .build()
+            .unwrap();
+        assert_eq!(name(&cli, "Title"), "X-Title");
+        assert_eq!(name(&cli, "Authorization"), "Authorization");
+        assert_eq!(name(&cli, "content-type"), "content-type");
+        assert_eq!(name(&cli, "X-Custom"), "X-Custom");
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{api_url, authorize, send, Client, NtfyError};
+
+/// The health of a ntfy server as reported by its `/v1/health` endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub struct Health {
+    /// Whether the server is healthy.
+    pub healthy: bool,
+}
+
+impl Client {
+    /// Checks the health of the server, e.g. before sending critical notifications.
+    ///
+    /// Uses the same HTTP client and credentials as publishing.
+    pub async fn health(&self) -> Result<Health, NtfyError> {
+        // the health endpoint only exists below `v1`, unlike the unversioned topic paths
+        let path = match self.api_version {
+            Some(_) => "health",
+            None => "v1/health",
+        };
+        let url = api_url(&self.server, self, path)?;
+        let req = authorize(self, self.http_client.get(url))?;
+        let body = send(self, req).await?.text().await?;
+
+        Ok(serde_json::from_str(&body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_server::{response, TestServer};
+    use crate::{Client, Health};
+
+    #[tokio::test]
+    async fn reports_the_server_health() {
+        let server = TestServer::start(vec![
+            response(200, &[], r#"{"healthy":true}"#),
+            response(200, &[], r#"{"healthy":false}"#),
+        ])
+        .await;
+        let cli = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+
+        assert_eq!(cli.health().await.unwrap(), Health { healthy: true });
+        assert_eq!(cli.health().await.unwrap(), Health { healthy: false });
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/v1/health");
+        assert_eq!(
+            requests[0].header("Authorization"),
+            Some("Bearer tk_secret")
+        );
+    }
+
+    #[tokio::test]
+    async fn checks_the_health_below_the_pinned_api_version() {
+        let server = TestServer::start(vec![response(200, &[], r#"{"healthy":true}"#)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .api_version("v2")
+            .build()
+            .unwrap();
+
+        cli.health().await.unwrap();
+
+        assert_eq!(server.requests()[0].path, "/v2/health");
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{action, validate, Action, Client, Message, NtfyError, Priority};
+
+/// A message in ntfy's [JSON publishing](https://docs.ntfy.sh/publish/#publish-as-json) format.
+#[derive(Debug, serde::Serialize)]
+struct JsonMessage<'a> {
+    topic: String,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<u8>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    actions: &'a [Action],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    click: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attach: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filename: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delay: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    markdown: bool,
+}
+
+/// Validates `msg` and serializes it into a JSON body for the topic of `cli`.
+pub(crate) fn body(cli: &Client, msg: &Message) -> Result<Vec<u8>, NtfyError> {
+    cli.topic.validate()?;
+    validate::tags(&msg.tags)?;
+    if msg.template || msg.message_template.is_some() {
+        return Err(NtfyError::InvalidInput(
+            "templating isn't supported for JSON publishing".into(),
+        ));
+    }
+    if msg.filename.is_some() && msg.attach.is_none() {
+        return Err(NtfyError::InvalidInput(
+            "a filename requires an attachment".into(),
+        ));
+    }
+    for url in [&msg.click, &msg.attach, &msg.icon].into_iter().flatten() {
+        reqwest::Url::parse(url)?;
+    }
+    if let Some(email) = &msg.email {
+        validate::email(email)?;
+    }
+    if let Some(call) = &msg.call {
+        validate::phone_number(call)?;
+    }
+    if msg.actions.len() > action::MAX_ACTIONS {
+        return Err(NtfyError::InvalidInput(format!(
+            "at most {} actions are allowed, got {}",
+            action::MAX_ACTIONS,
+            msg.actions.len()
+        )));
+    }
+
+    let json = JsonMessage {
+        topic: cli.topic.path_segment(),
+        message: &msg.message,
+        title: msg.title.as_deref(),
+        tags: &msg.tags,
+        priority: msg
+            .priority
+            .filter(|p| *p != Priority::Default)
+            .map(Priority::as_u8),
+        actions: &msg.actions,
+        click: msg.click.as_deref(),
+        attach: msg.attach.as_deref(),
+        filename: msg.filename.as_deref(),
+        icon: msg.icon.as_deref(),
+        email: msg.email.as_deref(),
+        call: msg.call.as_deref(),
+        delay: msg.delay.as_ref().map(|d| d.header_value()).transpose()?,
+        markdown: msg.markdown,
+    };
+
+    Ok(serde_json::to_vec(&json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_ntfys_json_shape() {
+        let cli = Client::new("https://ntfy.sh", "backup", "1234").unwrap();
+        let msg = Message::builder("Backup finished ✅")
+            .title("Backup")
+            .tags_slice(["floppy_disk", "ok"])
+            .priority(Priority::High)
+            .click("https://example.com")
+            .actions(vec![Action::View {
+                label: "Open".into(),
+                url: "https://example.com".into(),
+                clear: true,
+                extra: Default::default(),
+            }])
+            .markdown(true)
+            .build()
+            .unwrap();
+
+        let body: serde_json::Value = serde_json::from_slice(&body(&cli, &msg).unwrap()).unwrap();
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "topic": "backup_1234",
+                "message": "Backup finished ✅",
+                "title": "Backup",
+                "tags": ["floppy_disk", "ok"],
+                "priority": 4,
+                "actions": [
+                    {"action": "view", "label": "Open", "url": "https://example.com", "clear": true}
+                ],
+                "click": "https://example.com",
+                "markdown": true,
+            })
+        );
+    }
+
+    #[test]
+    fn omits_unset_fields() {
+        let cli = Client::new_topic("https://ntfy.sh", "backup").unwrap();
+        let msg = Message::builder("hi").build().unwrap();
+
+        assert_eq!(
+            body(&cli, &msg).unwrap(),
+            br#"{"topic":"backup","message":"hi"}"#
+        );
+    }
+
+    #[test]
+    fn rejects_filename_without_attachment() {
+        let cli = Client::new("https://ntfy.sh", "backup", "1234").unwrap();
+        let msg = Message::builder("hi").filename("a.txt").build_unchecked();
+
+        assert!(matches!(body(&cli, &msg), Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn rejects_templates() {
+        let cli = Client::new_topic("https://ntfy.sh", "backup").unwrap();
+        let msg = Message::builder("{}").template(true).build().unwrap();
+
+        assert!(
+            matches!(body(&cli, &msg), Err(NtfyError::InvalidInput(e)) if e.contains("templating"))
+        );
+    }
+}
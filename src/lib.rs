@@ -1,43 +1,226 @@
-/// Define the target for a notification.
-#[derive(Debug)]
-pub struct Client {
-    /// The URL of the ntfy server, e.g. `"https://ntfy.sh"`.
-    pub server: String,
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-    /// A topic which acts like a channel that receives notifications for that topic, e.g. `backup`.
-    pub topic: String,
+extern crate alloc;
 
-    /// A uuid (or another password like String) that will be appended to the topic for privacy reasons.
-    pub uuid: String,
-}
+mod action;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod click;
+#[cfg(feature = "client")]
+mod client;
+#[cfg(feature = "client")]
+mod config;
+mod delay;
+pub mod emoji;
+mod error;
+#[cfg(feature = "subscribe")]
+mod filter;
+mod header;
+#[cfg(feature = "client")]
+mod health;
+#[cfg(feature = "client")]
+mod json;
+#[cfg(feature = "client")]
+mod macros;
+#[cfg(feature = "client")]
+mod message_id;
+#[cfg(feature = "client")]
+mod mime;
+#[cfg(feature = "client")]
+mod notifier;
+#[cfg(feature = "subscribe")]
+mod poll;
+mod priority;
+#[cfg(feature = "client")]
+mod proxy;
+#[cfg(feature = "client")]
+mod publish;
+#[cfg(feature = "client")]
+mod publishing;
+#[cfg(feature = "client")]
+mod rate_limit;
+#[cfg(feature = "subscribe")]
+mod received;
+#[cfg(feature = "client")]
+mod redirect;
+#[cfg(feature = "client")]
+mod registry;
+#[cfg(feature = "client")]
+mod retry;
+#[cfg(feature = "subscribe")]
+mod since;
+#[cfg(feature = "subscribe")]
+mod subscribe;
+#[cfg(all(test, feature = "client"))]
+mod test_server;
+#[cfg(feature = "client")]
+mod topic;
+#[cfg(feature = "client")]
+mod transport;
+mod validate;
+#[cfg(feature = "ws")]
+mod ws;
 
-impl Client {
-    /// Constructs a new `Client`.
-    pub fn new(server: &str, topic: &str, uuid: &str) -> Self {
-        Self {
-            server: server.into(),
-            topic: topic.into(),
-            uuid: uuid.into(),
-        }
-    }
-}
+pub use action::{serialize_actions, Action, HttpAction};
+#[cfg(feature = "client")]
+pub use client::{Client, ClientBuilder};
+#[cfg(feature = "client")]
+pub use config::ClientConfig;
+pub use delay::{Delay, IntoDelay};
+pub use error::NtfyError;
+#[cfg(feature = "client")]
+pub use error::{NtfyServerError, RequestContext};
+#[cfg(feature = "subscribe")]
+pub use filter::SubscribeFilter;
+#[cfg(feature = "client")]
+pub use health::Health;
+#[cfg(feature = "client")]
+pub use message_id::MessageId;
+#[cfg(feature = "client")]
+pub use notifier::Notifier;
+#[cfg(feature = "subscribe")]
+pub use poll::{ntfy_confirmed, ntfy_idempotent, poll, poll_stream, Idempotent};
+pub use priority::{ParsePriorityError, Priority};
+#[cfg(feature = "client")]
+pub use proxy::Proxy;
+#[cfg(feature = "client")]
+pub use publish::PublishResponse;
+#[cfg(feature = "client")]
+pub(crate) use publishing::{api_url, authorization, authorize, execute, send, topic_url};
+#[cfg(feature = "client")]
+pub use publishing::{
+    ntfy, ntfy_event, ntfy_file, ntfy_file_streamed, ntfy_json, ntfy_many, ntfy_many_timeout,
+    ntfy_raw, ntfy_stream, ntfy_up, publish_batch,
+};
+#[cfg(feature = "subscribe")]
+pub use received::{Attachment, ReceivedMessage};
+#[cfg(feature = "client")]
+pub use redirect::Redirect;
+#[cfg(feature = "client")]
+pub use registry::TopicRegistry;
+#[cfg(feature = "client")]
+pub use retry::{Attempts, RetryPolicy};
+#[cfg(feature = "subscribe")]
+pub use since::Since;
+#[cfg(feature = "subscribe")]
+pub use subscribe::{
+    subscribe, subscribe_reconnecting, subscribe_since, subscribe_with_handle, SubscribeEvent,
+    SubscribeHandle,
+};
+#[cfg(feature = "client")]
+pub use topic::Topic;
+#[cfg(feature = "client")]
+pub use transport::Transport;
+#[cfg(feature = "test-util")]
+pub use transport::{MockTransport, RecordedRequest};
+#[cfg(feature = "ws")]
+pub use ws::subscribe_ws;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::time::Duration;
 
 /// Define the content of a notification.
-#[derive(Debug, Clone)]
+///
+/// With the `serde` feature enabled, messages can be serialized, e.g. to persist them.
+///
+/// Messages with the same content compare and hash equal, e.g. to skip identical alerts sent
+/// shortly before. The order of tags matters, as it determines the order of emojis in the title.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Message {
-    /// The title of the notification. Defaults to `server/topic_uuid` if `None`.
+    /// The title of the notification. The ntfy apps fall back to the topic name if `None`.
     title: Option<String>,
 
-    /// The body text of the notification.
+    /// The body text of the notification, ntfy shows `triggered` if empty.
     message: String,
 
-    /// One tag or multiple tags as a comma separated String, e.g. `important` or "foo,bar"`.
+    /// One tag or multiple tags, e.g. `["important"]` or `["foo", "bar"]`.
     ///
     /// If a tag matches an [emoji short code](https://docs.ntfy.sh/emojis/), it'll be converted to an emoji
     /// and prepended to title, otherwise it will be listed below the notification.
     ///
+    /// Will simply be ignored if empty.
+    tags: Vec<String>,
+
+    /// The priority of the notification. Defaults to `Priority::Default` if `None`.
+    priority: Option<Priority>,
+
+    /// A URL or deep link which is opened when the notification is tapped, see [click action](https://docs.ntfy.sh/publish/#click-action).
+    ///
+    /// Will simply be ignored if `None`.
+    click: Option<String>,
+
+    /// The URL of an externally hosted file which is attached to the notification, see [attachments](https://docs.ntfy.sh/publish/#attachments).
+    ///
+    /// Will simply be ignored if `None`.
+    attach: Option<String>,
+
+    /// Overrides the name of the attached file. Requires `attach` to be set.
+    filename: Option<String>,
+
+    /// Up to three action buttons, see [action buttons](https://docs.ntfy.sh/publish/#action-buttons).
+    actions: Vec<Action>,
+
+    /// Schedules the delivery of the notification, see [scheduled delivery](https://docs.ntfy.sh/publish/#scheduled-delivery).
+    ///
+    /// The notification is delivered immediately if `None`.
+    delay: Option<Delay>,
+
+    /// An email address the notification is forwarded to, see [e-mail notifications](https://docs.ntfy.sh/publish/#e-mail-notifications).
+    ///
+    /// Will simply be ignored if `None`.
+    email: Option<String>,
+
+    /// A phone number which is called to read the notification aloud, or `yes` to call the first verified number, see [phone calls](https://docs.ntfy.sh/publish/#phone-calls).
+    ///
     /// Will simply be ignored if `None`.
-    tags: Option<String>,
+    call: Option<String>,
+
+    /// The URL of an image which is used as notification icon, see [icons](https://docs.ntfy.sh/publish/#icons).
+    ///
+    /// Will simply be ignored if `None`.
+    icon: Option<String>,
+
+    /// Whether the body is rendered as Markdown, see [markdown formatting](https://docs.ntfy.sh/publish/#markdown-formatting).
+    markdown: bool,
+
+    /// Whether the notification is stored in the server's message cache, see [message caching](https://docs.ntfy.sh/publish/#message-caching).
+    cache: bool,
+
+    /// Whether the notification is forwarded to Firebase Cloud Messaging, see [disable Firebase](https://docs.ntfy.sh/publish/#disable-firebase).
+    firebase: bool,
+
+    /// Overrides the request timeout of the `Client` for this notification.
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    timeout: Option<Duration>,
+
+    /// Additional headers which aren't modeled by this crate.
+    headers: BTreeMap<String, String>,
+
+    /// Overrides the `Content-Type` of the body, e.g. `application/json`.
+    content_type: Option<String>,
+
+    /// Overrides the server of the `Client` for this notification.
+    server: Option<String>,
+
+    /// Whether the body is a template filled from JSON, see [message templating](https://docs.ntfy.sh/publish/#message-templating).
+    template: bool,
+
+    /// The template of the message text filled from the JSON body, sent as `Message` header.
+    message_template: Option<String>,
+    /// Whether line breaks in the body are kept, otherwise the lines are joined with spaces.
+    preserve_newlines: bool,
+
+    /// Whether the default tags of the `Client` are applied if the message has no tags.
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    use_default_tags: bool,
+
+    /// Whether the default priority of the `Client` is applied if the message has no priority.
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    use_default_priority: bool,
 }
 
 impl Message {
@@ -45,23 +228,241 @@ impl Message {
     pub fn builder(message: &str) -> MessageBuilder {
         MessageBuilder::new(message)
     }
+
+    /// Constructs a new `MessageBuilder` for a notification without body text, e.g. as a pure
+    /// signal like "backup started". ntfy shows `triggered` as text instead.
+    pub fn trigger() -> MessageBuilder {
+        MessageBuilder::new("")
+    }
+
+    /// Constructs a message republishing `received`, e.g. to relay messages from one topic to
+    /// another. Carries over the title, body, tags, priority, click URL and actions, while
+    /// server-assigned fields like the id and time are dropped.
+    ///
+    /// Base64 encoded bodies are decoded. Fails if the body isn't valid UTF-8 or the message is
+    /// rejected by `MessageBuilder::build`.
+    #[cfg(feature = "subscribe")]
+    pub fn from_received(received: &ReceivedMessage) -> Result<Message, NtfyError> {
+        let body = String::from_utf8(received.raw_body()?).map_err(|_| {
+            NtfyError::InvalidInput("binary message bodies can't be republished".into())
+        })?;
+        let mut builder = Message::builder(&body)
+            .title_opt(received.title.as_deref())
+            .tags_slice(&received.tags)
+            .actions(received.actions.clone());
+        if received.priority != Priority::Default {
+            builder = builder.priority(received.priority);
+        }
+        if let Some(click) = &received.click {
+            builder = builder.click(click);
+        }
+
+        builder.build()
+    }
+
+    /// Returns a copy of the message with `body` as text, e.g. to reuse a message with a fixed
+    /// title, tags and priority as template for many notifications. The lines of `body` are
+    /// joined like in `MessageBuilder::build` if line breaks aren't preserved.
+    pub fn with_body(&self, body: &str) -> Message {
+        Message {
+            message: join_lines(body.to_string(), self.preserve_newlines),
+            ..self.clone()
+        }
+    }
+
+    /// Serializes the message into the ntfy headers it is published with, e.g.
+    /// `("Title", "Backup")`, to send it without this crate's HTTP client. The overridden
+    /// `Content-Type` is included, the message body isn't.
+    ///
+    /// Available without the `std` and `client` features, e.g. to build messages on embedded
+    /// devices.
+    ///
+    /// # Errors
+    ///
+    /// Fails for the same invalid input as `MessageBuilder::build`, which only messages built
+    /// with `build_unchecked` can contain.
+    pub fn into_headers(self) -> Result<Vec<(String, String)>, NtfyError> {
+        self.preview_headers()
+    }
+
+    /// Returns the ntfy headers `ntfy()` sends for this message, encoded as they go over the
+    /// wire, without sending anything, e.g. to log a message before publishing it.
+    ///
+    /// Headers set by the client, like `Authorization` or the `X-` prefix, aren't included.
+    ///
+    /// # Errors
+    ///
+    /// Fails for the same invalid input as `MessageBuilder::build`, which only messages built
+    /// with `build_unchecked` can contain.
+    pub fn preview_headers(&self) -> Result<Vec<(String, String)>, NtfyError> {
+        let mut headers = headers(self)?;
+        if let Some(content_type) = &self.content_type {
+            headers.push(("Content-Type".into(), content_type.clone()));
+        }
+
+        Ok(headers)
+    }
+}
+
+/// Creates a message with only body text, e.g. `ntfy(&cli, "hello".into())`.
+impl From<&str> for Message {
+    fn from(message: &str) -> Self {
+        Message::builder(message).build_unchecked()
+    }
+}
+
+/// Creates a message with only body text.
+impl From<String> for Message {
+    fn from(message: String) -> Self {
+        Message {
+            message,
+            ..Message::from("")
+        }
+    }
+}
+
+/// Converts a received message into one republishing it, see `Message::from_received`.
+#[cfg(feature = "subscribe")]
+impl TryFrom<&ReceivedMessage> for Message {
+    type Error = NtfyError;
+
+    fn try_from(received: &ReceivedMessage) -> Result<Self, Self::Error> {
+        Message::from_received(received)
+    }
+}
+
+/// Converts a type of an application, e.g. an alert or event, into the notification about it.
+///
+/// Implement it once to map the fields of a type to the title, body, priority and tags of a
+/// notification, then publish values of it with `ntfy_event`.
+pub trait ToMessage {
+    /// Builds the notification for `self`.
+    fn to_message(&self) -> Message;
+}
+
+impl ToMessage for Message {
+    fn to_message(&self) -> Message {
+        self.clone()
+    }
+}
+
+/// Renders a one-line summary like `[title] message (priority: high, tags: a,b)`, omitting
+/// absent fields.
+impl core::fmt::Display for Message {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(title) = &self.title {
+            write!(f, "[{title}] ")?;
+        }
+        match self.message.as_str() {
+            "" => f.write_str("triggered")?,
+            message => f.write_str(message)?,
+        }
+
+        let mut details = Vec::new();
+        if let Some(priority) = self.priority.filter(|p| *p != Priority::Default) {
+            details.push(format!("priority: {priority}"));
+        }
+        if !self.tags.is_empty() {
+            details.push(format!("tags: {}", self.tags.join(",")));
+        }
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 pub struct MessageBuilder {
-    /// The title of the notification. Defaults to `server/topic_uuid` if `None`.
+    /// The title of the notification. The ntfy apps fall back to the topic name if `None`.
     title: Option<String>,
 
-    /// The body text of the notification.
+    /// The body text of the notification, ntfy shows `triggered` if empty.
     message: String,
 
-    /// One tag or multiple tags as a comma separated String, e.g. `important` or "foo,bar"`.
+    /// One tag or multiple tags, e.g. `["important"]` or `["foo", "bar"]`.
     ///
     /// If a tag matches an [emoji short code](https://docs.ntfy.sh/emojis/), it'll be converted to an emoji
     /// and prepended to title, otherwise it will be listed below the notification.
     ///
+    /// Will simply be ignored if empty.
+    tags: Vec<String>,
+
+    /// The priority of the notification. Defaults to `Priority::Default` if `None`.
+    priority: Option<Priority>,
+
+    /// A URL or deep link which is opened when the notification is tapped, see [click action](https://docs.ntfy.sh/publish/#click-action).
+    ///
     /// Will simply be ignored if `None`.
-    tags: Option<String>,
+    click: Option<String>,
+
+    /// The URL of an externally hosted file which is attached to the notification, see [attachments](https://docs.ntfy.sh/publish/#attachments).
+    ///
+    /// Will simply be ignored if `None`.
+    attach: Option<String>,
+
+    /// Overrides the name of the attached file. Requires `attach` to be set.
+    filename: Option<String>,
+
+    /// Up to three action buttons, see [action buttons](https://docs.ntfy.sh/publish/#action-buttons).
+    actions: Vec<Action>,
+
+    /// Schedules the delivery of the notification, see [scheduled delivery](https://docs.ntfy.sh/publish/#scheduled-delivery).
+    ///
+    /// The notification is delivered immediately if `None`.
+    delay: Option<Delay>,
+
+    /// An email address the notification is forwarded to, see [e-mail notifications](https://docs.ntfy.sh/publish/#e-mail-notifications).
+    ///
+    /// Will simply be ignored if `None`.
+    email: Option<String>,
+
+    /// A phone number which is called to read the notification aloud, or `yes` to call the first verified number, see [phone calls](https://docs.ntfy.sh/publish/#phone-calls).
+    ///
+    /// Will simply be ignored if `None`.
+    call: Option<String>,
+
+    /// The URL of an image which is used as notification icon, see [icons](https://docs.ntfy.sh/publish/#icons).
+    ///
+    /// Will simply be ignored if `None`.
+    icon: Option<String>,
+
+    /// Whether the body is rendered as Markdown, see [markdown formatting](https://docs.ntfy.sh/publish/#markdown-formatting).
+    markdown: bool,
+
+    /// Whether the notification is stored in the server's message cache, see [message caching](https://docs.ntfy.sh/publish/#message-caching).
+    cache: bool,
+
+    /// Whether the notification is forwarded to Firebase Cloud Messaging, see [disable Firebase](https://docs.ntfy.sh/publish/#disable-firebase).
+    firebase: bool,
+
+    /// Overrides the request timeout of the `Client` for this notification.
+    timeout: Option<Duration>,
+
+    /// Additional headers which aren't modeled by this crate.
+    headers: BTreeMap<String, String>,
+
+    /// Overrides the `Content-Type` of the body, e.g. `application/json`.
+    content_type: Option<String>,
+
+    /// Overrides the server of the `Client` for this notification.
+    server: Option<String>,
+
+    /// Whether the body is a template filled from JSON, see [message templating](https://docs.ntfy.sh/publish/#message-templating).
+    template: bool,
+
+    /// The template of the message text filled from the JSON body, sent as `Message` header.
+    message_template: Option<String>,
+
+    /// Whether line breaks in the body are kept, otherwise the lines are joined with spaces.
+    preserve_newlines: bool,
+
+    /// Whether the default tags of the `Client` are applied if the message has no tags.
+    use_default_tags: bool,
+
+    /// Whether the default priority of the `Client` is applied if the message has no priority.
+    use_default_priority: bool,
 }
 
 impl MessageBuilder {
@@ -69,10 +470,39 @@ impl MessageBuilder {
         MessageBuilder {
             title: None,
             message: message.to_string(),
-            tags: None,
+            tags: Vec::new(),
+            priority: None,
+            click: None,
+            attach: None,
+            filename: None,
+            actions: Vec::new(),
+            delay: None,
+            email: None,
+            call: None,
+            icon: None,
+            markdown: false,
+            cache: true,
+            firebase: true,
+            timeout: None,
+            headers: BTreeMap::new(),
+            content_type: None,
+            server: None,
+            template: false,
+            message_template: None,
+            preserve_newlines: true,
+            use_default_tags: true,
+            use_default_priority: true,
         }
     }
 
+    /// Applies builder methods through a mutable reference, to configure a builder incrementally
+    /// without reassigning it, e.g. `if urgent { builder.update(|b| b.priority(Priority::Max)); }`.
+    pub fn update(&mut self, f: impl FnOnce(Self) -> Self) -> &mut Self {
+        let builder = core::mem::replace(self, MessageBuilder::new(""));
+        *self = f(builder);
+        self
+    }
+
     /// Adds an optional title to the message.
     pub fn title(self, title: &str) -> MessageBuilder {
         MessageBuilder {
@@ -81,47 +511,841 @@ impl MessageBuilder {
         }
     }
 
+    /// Adds a title to the message if `title` is `Some`, otherwise leaves it unchanged.
+    pub fn title_opt(self, title: Option<&str>) -> Self {
+        match title {
+            Some(title) => self.title(title),
+            None => self,
+        }
+    }
+
     /// Adds one or more optional tags to the message, e.g. `"foo"` or "foo,bar"`.
+    ///
+    /// Tags are trimmed, empty and duplicate tags are dropped while keeping the order.
     pub fn tags(self, tags: &str) -> Self {
         Self {
-            tags: Some(tags.to_string()),
+            tags: normalize_tags(tags.split(',')),
+            ..self
+        }
+    }
+
+    /// Adds tags to the message like `tags` if `tags` is `Some`, otherwise leaves them unchanged.
+    pub fn tags_opt(self, tags: Option<&str>) -> Self {
+        match tags {
+            Some(tags) => self.tags(tags),
+            None => self,
+        }
+    }
+
+    /// Adds one or more optional tags to the message, e.g. `&["foo", "bar"]`, without joining
+    /// them manually. Tags are trimmed, empty and duplicate tags are dropped while keeping the
+    /// order.
+    ///
+    /// Tags containing a comma or control characters are rejected when sending, as well as more
+    /// than 32 tags or tags longer than 1024 bytes when joined.
+    pub fn tags_slice<I, T>(self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        Self {
+            tags: normalize_tags(tags),
+            ..self
+        }
+    }
+
+    /// Checks that all tags wrapped in colons like `:warning:` are known emoji short codes, which
+    /// catches typos like `:warnign:` that would otherwise silently be shown as text.
+    pub fn validate_tags(&self) -> Result<(), NtfyError> {
+        match self
+            .tags
+            .iter()
+            .find(|tag| emoji::looks_like_short_code(tag) && !emoji::is_short_code(tag))
+        {
+            Some(tag) => Err(NtfyError::InvalidInput(format!(
+                "unknown emoji short code `{tag}`"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets an optional priority for the message.
+    pub fn priority(self, priority: Priority) -> Self {
+        Self {
+            priority: Some(priority),
+            ..self
+        }
+    }
+
+    /// Sets an optional URL which is opened when the notification is tapped, e.g. `"https://example.com"`
+    /// or an Android `intent://` link.
+    pub fn click(self, url: &str) -> Self {
+        Self {
+            click: Some(url.to_string()),
+            ..self
+        }
+    }
+
+    /// Attaches an externally hosted file to the message via its URL.
+    pub fn attach(self, url: &str) -> Self {
+        Self {
+            attach: Some(url.to_string()),
+            ..self
+        }
+    }
+
+    /// Overrides the name of the attached file, e.g. `"backup.log"`.
+    pub fn filename(self, name: &str) -> Self {
+        Self {
+            filename: Some(name.to_string()),
+            ..self
+        }
+    }
+
+    /// Adds up to three action buttons to the message.
+    pub fn actions(self, actions: Vec<Action>) -> Self {
+        Self { actions, ..self }
+    }
+
+    /// Delays the delivery of the message by a duration like `"30min"`, `"2h"` or
+    /// `Duration::from_secs(60)`, or until a point in time like a `chrono::DateTime` or a Unix
+    /// timestamp like `1700000000` or `"1700000000"`, see `IntoDelay`.
+    /// Natural-language forms like `"9am"` or `"tomorrow, 10am"` are passed to the server
+    /// unchecked.
+    ///
+    /// ntfy allows delays from 10 seconds up to three days, shorter and longer delays as well as
+    /// timestamps in the past are rejected by `build`.
+    pub fn delay(self, delay: impl IntoDelay) -> Self {
+        Self {
+            delay: Some(delay.into_delay()),
+            ..self
+        }
+    }
+
+    /// Delays the delivery of the message until the given point in time, which is converted to a
+    /// Unix timestamp regardless of its time zone. Points in the past are rejected by `build`.
+    #[cfg(feature = "std")]
+    pub fn delay_until<Tz: chrono::TimeZone>(self, at: chrono::DateTime<Tz>) -> Self {
+        Self {
+            delay: Some(at.into_delay()),
+            ..self
+        }
+    }
+
+    /// Forwards the message to an email address, e.g. `"phil@example.com"`.
+    ///
+    /// The ntfy server needs to have email sending configured, and may require the publisher
+    /// to be authenticated.
+    pub fn email(self, address: &str) -> Self {
+        Self {
+            email: Some(address.to_string()),
+            ..self
+        }
+    }
+
+    /// Forwards the message to the given email addresses.
+    ///
+    /// ntfy only accepts a single address per message, so building or sending a message with
+    /// more than one address fails with `NtfyError::InvalidInput`. Configure an alias on the mail
+    /// server to notify a distribution list instead. An empty slice clears the address.
+    pub fn emails(self, addresses: &[&str]) -> Self {
+        Self {
+            email: (!addresses.is_empty()).then(|| addresses.join(", ")),
             ..self
         }
     }
 
-    /// Create a `Message` from a `MessageBuilder`.
-    pub fn build(self) -> Message {
+    /// Places a phone call reading the message aloud, either to a number like `"+12223334444"` or,
+    /// with `"yes"`, to the first verified number of the account.
+    ///
+    /// This requires an authenticated ntfy account with a plan that includes phone calls and a
+    /// verified phone number, the server will reject the message otherwise.
+    pub fn call(self, number: &str) -> Self {
+        Self {
+            call: Some(number.to_string()),
+            ..self
+        }
+    }
+
+    /// Sets the URL of an image which is used as notification icon, e.g. a JPEG or PNG logo.
+    pub fn icon(self, url: &str) -> Self {
+        Self {
+            icon: Some(url.to_string()),
+            ..self
+        }
+    }
+
+    /// Enables Markdown formatting of the message body.
+    ///
+    /// Markdown is only rendered by the web and desktop apps, other clients show the raw text.
+    pub fn markdown(self, enabled: bool) -> Self {
+        Self {
+            markdown: enabled,
+            ..self
+        }
+    }
+
+    /// Treats the title and the message template as templates filled from the message body,
+    /// e.g. `title("{{.alert.title}}")`. The body must then be the JSON payload, e.g. a webhook
+    /// of another service, instead of the text of the notification.
+    ///
+    /// Works alongside `markdown` and `content_type`. Not supported by `ntfy_json` and file
+    /// uploads, as the body is the message itself or the file there.
+    pub fn template(self, enabled: bool) -> Self {
+        Self {
+            template: enabled,
+            ..self
+        }
+    }
+
+    /// Sets the template of the message text, e.g. `"{{.alert.message}} on {{.host}}"`, which
+    /// ntfy fills from the JSON payload in the body. Sent as `Message` header and only valid
+    /// with `template(true)`, see `template`.
+    pub fn message_template(self, template: &str) -> Self {
+        Self {
+            message_template: Some(template.to_string()),
+            ..self
+        }
+    }
+
+    /// Overrides the `Content-Type` of the body, e.g. `application/json` for content parsed by an
+    /// app. Takes precedence over the type guessed for file uploads.
+    ///
+    /// Markdown is still enabled via `markdown`, which works with any content type.
+    pub fn content_type(self, content_type: &str) -> Self {
+        Self {
+            content_type: Some(content_type.to_string()),
+            ..self
+        }
+    }
+
+    /// Disables caching of the message on the server if `false`, so it's only delivered to
+    /// connected subscribers and never returned by polling. Messages are cached by default.
+    pub fn cache(self, enabled: bool) -> Self {
+        Self {
+            cache: enabled,
+            ..self
+        }
+    }
+
+    /// Disables forwarding of the message to Firebase Cloud Messaging if `false`, so Android
+    /// clients only receive it via their own connection to the server.
+    ///
+    /// This is meant for self-hosted servers with Firebase configured, it has no effect on ntfy.sh.
+    pub fn firebase(self, enabled: bool) -> Self {
+        Self {
+            firebase: enabled,
+            ..self
+        }
+    }
+
+    /// Overrides the request timeout of the `Client` when sending this message.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Sends this notification to another server than the one of the `Client`, e.g. a fallback,
+    /// reusing its topic, uuid and credentials.
+    pub fn server(self, url: &str) -> Self {
+        Self {
+            server: Some(url.to_string()),
+            ..self
+        }
+    }
+
+    /// Controls whether line breaks in the body are kept, which is the default.
+    ///
+    /// The body is sent verbatim as UTF-8 request body, so real line breaks are rendered as such
+    /// by the ntfy apps, while an escaped `\n` is shown literally. File uploads send the body as
+    /// `Message` header instead, with line breaks escaped as `\n` which ntfy turns back into
+    /// line breaks. If disabled, the lines are joined with single spaces, e.g. to show a
+    /// multiline log excerpt on one line.
+    pub fn preserve_newlines(self, preserve: bool) -> Self {
+        Self {
+            preserve_newlines: preserve,
+            ..self
+        }
+    }
+
+    /// Sends the message without the default tags of the `Client` if it has no tags itself,
+    /// see `ClientBuilder::default_tags`.
+    pub fn no_default_tags(self) -> Self {
+        Self {
+            use_default_tags: false,
+            ..self
+        }
+    }
+
+    /// Sends the message without the default priority of the `Client` if it has no priority
+    /// itself, see `ClientBuilder::default_priority`.
+    pub fn no_default_priority(self) -> Self {
+        Self {
+            use_default_priority: false,
+            ..self
+        }
+    }
+
+    /// Sends the message without the default tags and priority of the `Client`, see
+    /// `no_default_tags` and `no_default_priority`.
+    pub fn clear_defaults(self) -> Self {
+        self.no_default_tags().no_default_priority()
+    }
+
+    /// Adds a custom header to the message, e.g. for experimental or deployment specific features.
+    ///
+    /// Headers managed by this crate, like `Title`, can't be set this way and are rejected when
+    /// sending.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Create a `Message` from a `MessageBuilder`, validating all input up front.
+    ///
+    /// Fails if a URL can't be parsed, a tag contains a comma, the email address or phone number
+    /// is invalid, the delay is out of range, the actions can't be serialized or a custom header
+    /// is managed by this crate.
+    pub fn build(self) -> Result<Message, NtfyError> {
+        let msg = self.build_unchecked();
+        if let Some(server) = &msg.server {
+            validate::url(server)?;
+        }
+        headers(&msg)?;
+        Ok(msg)
+    }
+
+    /// Create a `Message` from a `MessageBuilder` without validating it, invalid input is
+    /// rejected when sending instead.
+    pub fn build_unchecked(self) -> Message {
         Message {
             title: self.title,
-            message: self.message,
+            message: join_lines(self.message, self.preserve_newlines),
             tags: self.tags,
+            priority: self.priority,
+            click: self.click,
+            attach: self.attach,
+            filename: self.filename,
+            actions: self.actions,
+            delay: self.delay,
+            email: self.email,
+            call: self.call,
+            icon: self.icon,
+            markdown: self.markdown,
+            cache: self.cache,
+            firebase: self.firebase,
+            timeout: self.timeout,
+            headers: self.headers,
+            content_type: self.content_type,
+            server: self.server,
+            template: self.template,
+            message_template: self.message_template,
+            preserve_newlines: self.preserve_newlines,
+            use_default_tags: self.use_default_tags,
+            use_default_priority: self.use_default_priority,
         }
     }
 }
 
-/// Sends a notifications and returns the response of the POST request.
-pub async fn ntfy(
-    cli: &Client,
-    msg: Message,
-) -> Result<reqwest::Response, Box<dyn std::error::Error>> {
-    let url = format!("{}/{}_{}", cli.server, cli.topic, cli.uuid);
-    let title = match msg.title {
-        Some(s) => s,
-        None => "".into(),
-    };
-    let tags = match msg.tags {
-        Some(s) => s,
-        None => "".into(),
-    };
-    let client = reqwest::Client::new();
-
-    let res = client
-        .post(url)
-        .header("Title", title)
-        .header("Tags", tags)
-        .body(msg.message)
-        .send()
-        .await?;
-
-    Ok(res)
+/// Joins the lines of `body` with single spaces unless `preserve` is set.
+fn join_lines(body: String, preserve: bool) -> String {
+    match preserve {
+        true => body,
+        false => body.lines().collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// Trims `tags` and drops empty and duplicate tags, keeping the first occurrence.
+pub(crate) fn normalize_tags<I, T>(tags: I) -> Vec<String>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    let mut unique: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.as_ref().trim();
+        if !tag.is_empty() && !unique.iter().any(|t| t == tag) {
+            unique.push(tag.to_string());
+        }
+    }
+
+    unique
+}
+
+/// Validates `msg` and returns the ntfy headers representing it.
+pub(crate) fn headers(msg: &Message) -> Result<Vec<(String, String)>, NtfyError> {
+    let mut headers = Vec::new();
+    let mut push = |name: &str, value: String| headers.push((name.to_string(), value));
+
+    validate::tags(&msg.tags)?;
+    if !msg.tags.is_empty() {
+        push("Tags", header::encode_value(&msg.tags.join(",")));
+    }
+    if let Some(title) = &msg.title {
+        push("Title", header::encode_value(title));
+    }
+    if let Some(p) = msg.priority.filter(|p| *p != Priority::Default) {
+        push("Priority", p.as_u8().to_string());
+    }
+    if let Some(click) = &msg.click {
+        validate::url(click)?;
+        push("Click", click.clone());
+    }
+    if let Some(attach) = &msg.attach {
+        validate::url(attach)?;
+        push("Attach", attach.clone());
+        if let Some(filename) = &msg.filename {
+            push("Filename", header::encode_value(filename));
+        }
+    } else if msg.filename.is_some() {
+        return Err(NtfyError::InvalidInput(
+            "a filename requires an attachment".into(),
+        ));
+    }
+    if let Some(icon) = &msg.icon {
+        validate::url(icon)?;
+        push("Icon", icon.clone());
+    }
+    if let Some(email) = &msg.email {
+        validate::email(email)?;
+        push("Email", email.clone());
+    }
+    if let Some(call) = &msg.call {
+        validate::phone_number(call)?;
+        push("Call", call.clone());
+    }
+    if msg.markdown {
+        push("Markdown", "yes".into());
+    }
+    if msg.template {
+        push("Template", "yes".into());
+    }
+    if let Some(template) = &msg.message_template {
+        if !msg.template {
+            return Err(NtfyError::InvalidInput(
+                "a message template requires `template(true)`".into(),
+            ));
+        }
+        push("Message", header::encode_value(&escape_newlines(template)));
+    }
+    if !msg.cache {
+        push("Cache", "no".into());
+    }
+    if !msg.firebase {
+        push("Firebase", "no".into());
+    }
+    if let Some(delay) = &msg.delay {
+        push("Delay", delay.header_value()?);
+    }
+    if !msg.actions.is_empty() {
+        push(
+            "Actions",
+            header::encode_value(&action::serialize_actions(&msg.actions)?),
+        );
+    }
+
+    for (name, value) in custom_headers(msg)? {
+        push(name, value.clone());
+    }
+
+    Ok(headers)
+}
+
+/// Escapes line breaks as `\n` for the `Message` header, which ntfy turns back into line breaks.
+pub(crate) fn escape_newlines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "\\n")
+}
+
+/// Returns the custom headers of `msg`, rejecting those managed by this crate.
+pub(crate) fn custom_headers(msg: &Message) -> Result<&BTreeMap<String, String>, NtfyError> {
+    if let Some(name) = msg.headers.keys().find(|name| header::is_managed(name)) {
+        return Err(NtfyError::InvalidInput(format!(
+            "the `{name}` header is managed by ntfy-rs and can't be set manually"
+        )));
+    }
+
+    Ok(&msg.headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_messages_through_json() {
+        let msg = Message::builder("Backup finished")
+            .title("Backup")
+            .tags_slice(["floppy_disk"])
+            .priority(Priority::High)
+            .click("https://example.com")
+            .attach("https://example.com/log.txt")
+            .filename("log.txt")
+            .actions(vec![Action::View {
+                label: "Open".into(),
+                url: "https://example.com".into(),
+                clear: false,
+                extra: BTreeMap::new(),
+            }])
+            .delay("30min")
+            .email("me@example.com")
+            .icon("https://example.com/icon.png")
+            .markdown(true)
+            .cache(false)
+            .firebase(false)
+            .timeout(std::time::Duration::from_secs(5))
+            .header("X-Custom", "on")
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&msg).unwrap();
+        let restored: Message = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&restored).unwrap(), json);
+        assert_eq!(json["title"], "Backup");
+        assert_eq!(json["priority"], "high");
+        assert_eq!(json["tags"][0], "floppy_disk");
+    }
+
+    #[test]
+    fn displays_one_line_summaries() {
+        let plain = Message::builder("Backup finished").build().unwrap();
+        let full = Message::builder("Backup finished")
+            .title("Backup")
+            .priority(Priority::High)
+            .tags("floppy_disk,ok")
+            .build()
+            .unwrap();
+        let tagged = Message::builder("Disk full")
+            .priority(Priority::Default)
+            .tags_slice(["warning"])
+            .build()
+            .unwrap();
+
+        assert_eq!(plain.to_string(), "Backup finished");
+        assert_eq!(
+            full.to_string(),
+            "[Backup] Backup finished (priority: high, tags: floppy_disk,ok)"
+        );
+        assert_eq!(tagged.to_string(), "Disk full (tags: warning)");
+    }
+
+    #[test]
+    fn validates_emoji_tags() {
+        let builder = Message::builder("hi").tags("backup,:warning:");
+        assert!(builder.validate_tags().is_ok());
+
+        let builder = Message::builder("hi").tags("backup,:warnign:");
+        assert!(
+            matches!(builder.validate_tags(), Err(NtfyError::InvalidInput(e)) if e.contains(":warnign:"))
+        );
+    }
+
+    #[test]
+    fn validates_messages_at_build_time() {
+        let invalid = |builder: MessageBuilder| match builder.build() {
+            Err(NtfyError::InvalidInput(e)) => e,
+            res => panic!("unexpected result: {res:?}"),
+        };
+
+        assert!(invalid(Message::builder("hi").tags("a").tags_slice(["a,b"])).contains("comma"));
+        assert!(invalid(Message::builder("hi").email("not an address")).contains("email"));
+        assert!(invalid(Message::builder("hi").filename("a.txt")).contains("attachment"));
+        #[cfg(feature = "std")]
+        assert!(invalid(Message::builder("hi").delay("1s")).contains("minimum"));
+        assert!(invalid(Message::builder("hi").header("Title", "x")).contains("Title"));
+        #[cfg(feature = "client")]
+        assert!(matches!(
+            Message::builder("hi").click("not a url").build(),
+            Err(NtfyError::Url(_))
+        ));
+        #[cfg(not(feature = "client"))]
+        assert!(invalid(Message::builder("hi").click("not a url")).contains("URL"));
+        assert!(Message::builder("hi")
+            .click("https://example.com")
+            .delay("30min")
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn builds_unchecked_messages() {
+        let msg = Message::builder("hi").click("not a url").build_unchecked();
+        assert_eq!(msg.click.as_deref(), Some("not a url"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn normalizes_delays_across_time_zones() {
+        let at = chrono::Utc::now() + chrono::Duration::hours(2);
+        let local = at.with_timezone(&chrono::FixedOffset::east_opt(5 * 3600).unwrap());
+        let msg = Message::builder("hi").delay_until(local).build().unwrap();
+        assert!(matches!(msg.delay, Some(Delay::At(ts)) if ts == at.timestamp()));
+
+        let past = chrono::Utc::now() - chrono::Duration::minutes(1);
+        assert!(matches!(
+            Message::builder("hi").delay_until(past).build(),
+            Err(NtfyError::InvalidInput(e)) if e.contains("past")
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_server_overrides() {
+        let res = Message::builder("hi").server("not a url").build();
+        #[cfg(feature = "client")]
+        assert!(matches!(res, Err(NtfyError::Url(_))));
+        #[cfg(not(feature = "client"))]
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn sets_optional_titles_and_tags() {
+        let unset = Message::builder("hi")
+            .title("kept")
+            .tags("a")
+            .title_opt(None)
+            .tags_opt(None)
+            .build()
+            .unwrap();
+        assert_eq!(unset.title.as_deref(), Some("kept"));
+        assert_eq!(unset.tags, ["a"]);
+
+        let none = Message::builder("hi").title_opt(None).build().unwrap();
+        assert_eq!(none.title, None);
+
+        let set = Message::builder("hi")
+            .title_opt(Some("x"))
+            .tags_opt(Some("b,c"))
+            .build()
+            .unwrap();
+        assert_eq!(set.title.as_deref(), Some("x"));
+        assert_eq!(set.tags, ["b", "c"]);
+    }
+
+    #[test]
+    fn normalizes_tags_in_order() {
+        let msg = Message::builder("hi").tags("a,b,a,,c").build().unwrap();
+        assert_eq!(msg.tags.join(","), "a,b,c");
+
+        let msg = Message::builder("hi")
+            .tags_slice([" b", "a", "", "b ", "a"])
+            .build()
+            .unwrap();
+        assert_eq!(msg.tags, ["b", "a"]);
+    }
+
+    #[test]
+    fn rejects_invalid_tag_lists_before_sending() {
+        let many: Vec<_> = (0..100).map(|i| format!("tag{i}")).collect();
+        let res = Message::builder("hi").tags_slice(&many).build();
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+
+        let res = Message::builder("hi")
+            .tags_slice(["ok", "two\nlines"])
+            .build();
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("control")));
+    }
+
+    #[test]
+    fn accepts_a_single_email_address_only() {
+        let msg = Message::builder("hi")
+            .emails(&["phil@example.com"])
+            .build()
+            .unwrap();
+        assert_eq!(msg.email.as_deref(), Some("phil@example.com"));
+
+        let msg = Message::builder("hi")
+            .email("a@example.com")
+            .emails(&[])
+            .build();
+        assert!(msg.unwrap().email.is_none());
+
+        let res = Message::builder("hi")
+            .emails(&["a@example.com", "b@example.com"])
+            .build();
+        assert!(
+            matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("single email address"))
+        );
+    }
+
+    #[test]
+    fn serializes_headers_without_the_client() {
+        let msg = Message::builder("Disk full")
+            .title("Backup")
+            .tags("warning")
+            .priority(Priority::High)
+            .click("https://example.com")
+            .content_type("text/plain")
+            .header("X-Trace", "42")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            msg.into_headers().unwrap(),
+            [
+                ("Tags", "warning"),
+                ("Title", "Backup"),
+                ("Priority", "4"),
+                ("Click", "https://example.com"),
+                ("X-Trace", "42"),
+                ("Content-Type", "text/plain"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+
+        let invalid = Message::builder("hi").filename("a.txt").build_unchecked();
+        assert!(matches!(
+            invalid.into_headers(),
+            Err(NtfyError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn previews_encoded_headers_without_consuming_the_message() {
+        let msg = Message::builder("Disk full")
+            .title("Café backup")
+            .priority(Priority::Max)
+            .build()
+            .unwrap();
+
+        let preview = msg.preview_headers().unwrap();
+        assert_eq!(preview[0].0, "Title");
+        assert!(preview[0].1.starts_with("=?UTF-8?B?"));
+        assert_eq!(preview[1], ("Priority".to_string(), "5".to_string()));
+        assert!(preview.iter().all(|(name, _)| name != "Tags"));
+        assert_eq!(msg.into_headers().unwrap(), preview);
+    }
+
+    #[test]
+    fn configures_builders_through_mutable_references() {
+        for (urgent, failed) in [(false, false), (true, true)] {
+            let mut builder = Message::builder("Backup finished");
+            if urgent {
+                builder.update(|b| b.priority(Priority::Max));
+            }
+            if failed {
+                builder
+                    .update(|b| b.title("Backup failed"))
+                    .update(|b| b.tags("warning"));
+            }
+            let msg = builder.build().unwrap();
+
+            assert_eq!(msg.message, "Backup finished");
+            assert_eq!(msg.priority, urgent.then_some(Priority::Max));
+            assert_eq!(msg.title.is_some(), failed);
+            assert_eq!(msg.tags.is_empty(), !failed);
+        }
+    }
+
+    #[test]
+    fn sends_the_template_header_only_if_enabled() {
+        let header = |builder: MessageBuilder| {
+            let headers = builder.build().unwrap().into_headers().unwrap();
+            headers
+                .into_iter()
+                .find(|(name, _)| name == "Template")
+                .map(|(_, value)| value)
+        };
+
+        assert_eq!(header(Message::builder("{}")), None);
+        assert_eq!(header(Message::builder("{}").template(false)), None);
+        assert_eq!(
+            header(Message::builder("{}").template(true).markdown(true)),
+            Some("yes".into())
+        );
+        assert!(Message::builder("hi").header("tpl", "yes").build().is_err());
+    }
+
+    #[test]
+    fn sends_message_templates_with_templating_only() {
+        let msg = Message::builder(r#"{"alert":{"message":"disk full"}}"#)
+            .template(true)
+            .title("{{.alert.title}}")
+            .message_template("{{.alert.message}}\non {{.host}}")
+            .build()
+            .unwrap();
+        let headers = msg.into_headers().unwrap();
+        assert!(headers.contains(&("Message".into(), r"{{.alert.message}}\non {{.host}}".into())));
+
+        let res = Message::builder("{}").message_template("{{.x}}").build();
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("template(true)")));
+    }
+
+    #[test]
+    fn preserves_or_joins_line_breaks() {
+        let text = "Backup failed:\r\ndisk full\nretrying";
+
+        let kept = Message::builder(text).build().unwrap();
+        assert_eq!(kept.message.as_bytes(), text.as_bytes());
+
+        let joined = Message::builder(text)
+            .preserve_newlines(false)
+            .build()
+            .unwrap();
+        assert_eq!(
+            joined.message.as_bytes(),
+            b"Backup failed: disk full retrying"
+        );
+
+        assert_eq!(
+            escape_newlines(text),
+            r"Backup failed:\ndisk full\nretrying"
+        );
+    }
+
+    #[test]
+    fn derives_messages_from_a_template() {
+        let template = Message::builder("unused")
+            .title("Backup")
+            .tags("floppy_disk")
+            .priority(Priority::High)
+            .click("https://backup.example.com")
+            .preserve_newlines(false)
+            .build()
+            .unwrap();
+
+        let msg = template.with_body("host-1 done\nin 42s");
+
+        assert_eq!(msg.message, "host-1 done in 42s");
+        assert_eq!(msg.title, template.title);
+        assert_eq!(msg.tags, template.tags);
+        assert_eq!(msg.priority, Some(Priority::High));
+        assert_eq!(msg.click, template.click);
+        assert_eq!(template.message, "unused");
+        assert_eq!(
+            msg.preview_headers().unwrap(),
+            template.preview_headers().unwrap()
+        );
+    }
+
+    #[test]
+    fn hashes_messages_by_content() {
+        extern crate std;
+        use std::collections::HashSet;
+
+        let alert = || {
+            Message::builder("Disk full")
+                .title("Alert")
+                .tags(" warning,floppy_disk,warning")
+                .priority(Priority::High)
+        };
+        let mut sent = HashSet::new();
+
+        assert!(sent.insert(alert().build().unwrap()));
+        assert!(!sent.insert(alert().build().unwrap()));
+        assert_eq!(
+            alert().build().unwrap(),
+            Message::builder("Disk full")
+                .title("Alert")
+                .tags("warning,floppy_disk")
+                .priority(Priority::High)
+                .build()
+                .unwrap()
+        );
+        assert!(sent.insert(alert().priority(Priority::Max).build().unwrap()));
+        assert!(sent.insert(alert().click("https://example.com").build().unwrap()));
+        assert!(sent.insert(alert().tags("floppy_disk,warning").build().unwrap()));
+    }
 }
@@ -0,0 +1,114 @@
+/// Sends a notification built from a body and optional named fields, e.g.
+/// `notify!(cli, "Backup finished", title: "Done", tags: "white_check_mark", priority: high)`,
+/// and evaluates to the future returned by [`ntfy`](crate::ntfy).
+///
+/// The named fields may come before or after the body and are passed to the `MessageBuilder`
+/// method of the same name, i.e. `click: url` expands to `.click(url)`. The priority also
+/// accepts the names `min`, `low`, `default`, `high`, `max` and `urgent`. The message is
+/// validated like `MessageBuilder::build`, invalid messages fail the future without sending.
+#[macro_export]
+macro_rules! notify {
+    ($cli:expr, $($args:tt)+) => {
+        $crate::__notify!(@parse $cli, [] [] $($args)+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __notify {
+    (@parse $cli:expr, [$($body:expr)?] [$($fields:tt)*] priority : $p:ident $(, $($rest:tt)*)?) => {
+        $crate::__notify!(
+            @parse $cli, [$($body)?] [$($fields)* (priority $crate::__notify!(@priority $p))] $($($rest)*)?
+        )
+    };
+    (@parse $cli:expr, [$($body:expr)?] [$($fields:tt)*] $key:ident : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__notify!(@parse $cli, [$($body)?] [$($fields)* ($key $value)] $($($rest)*)?)
+    };
+    (@parse $cli:expr, [] [$($fields:tt)*] $body:expr $(, $($rest:tt)*)?) => {
+        $crate::__notify!(@parse $cli, [$body] [$($fields)*] $($($rest)*)?)
+    };
+    (@parse $cli:expr, [] [$($fields:tt)*]) => {
+        ::core::compile_error!("notify! requires a message body, e.g. `notify!(cli, \"Done\")`")
+    };
+    (@parse $cli:expr, [$body:expr] [$(($key:ident $value:expr))*]) => {{
+        let cli: &$crate::Client = &$cli;
+        let msg = $crate::Message::builder($body) $(.$key($value))* .build();
+        async move { $crate::ntfy(cli, msg?).await }
+    }};
+
+    (@priority min) => { $crate::Priority::Min };
+    (@priority low) => { $crate::Priority::Low };
+    (@priority default) => { $crate::Priority::Default };
+    (@priority high) => { $crate::Priority::High };
+    (@priority max) => { $crate::Priority::Max };
+    (@priority urgent) => { $crate::Priority::Max };
+    (@priority $p:ident) => { $p };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_server::{response, TestServer, PUBLISHED};
+    use crate::{Client, NtfyError, Priority};
+
+    #[tokio::test]
+    async fn sends_the_body_with_named_fields() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        notify!(cli, "Backup finished").await.unwrap();
+        notify!(
+            cli,
+            title: "Done",
+            "Backup finished",
+            tags: "white_check_mark",
+            priority: high,
+        )
+        .await
+        .unwrap();
+        let priority = Priority::Low;
+        notify!(&cli, "Disk at 80%", priority: priority, click: "https://example.com")
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].body, b"Backup finished");
+        assert_eq!(requests[0].header("Title"), None);
+        assert_eq!(requests[1].body, b"Backup finished");
+        assert_eq!(requests[1].header("Title"), Some("Done"));
+        assert_eq!(requests[1].header("Tags"), Some("white_check_mark"));
+        assert_eq!(requests[1].header("Priority"), Some("4"));
+        assert_eq!(requests[2].header("Priority"), Some("2"));
+        assert_eq!(requests[2].header("Click"), Some("https://example.com"));
+    }
+
+    #[tokio::test]
+    async fn accepts_all_priority_names() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        notify!(cli, "hi", priority: min).await.unwrap();
+        notify!(cli, "hi", priority: low).await.unwrap();
+        notify!(cli, "hi", priority: default).await.unwrap();
+        notify!(cli, "hi", priority: max).await.unwrap();
+        notify!(cli, "hi", priority: urgent).await.unwrap();
+
+        let priorities: Vec<_> = server
+            .requests()
+            .iter()
+            .map(|req| req.header("Priority").map(str::to_string))
+            .collect();
+        let expected = [Some("1"), Some("2"), None, Some("5"), Some("5")];
+        assert_eq!(priorities, expected.map(|p| p.map(str::to_string)));
+    }
+
+    #[tokio::test]
+    async fn fails_invalid_messages_without_sending() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let res = notify!(cli, "hi", click: "not a url").await;
+
+        assert!(matches!(res, Err(NtfyError::Url(_))));
+        assert!(server.requests().is_empty());
+    }
+}
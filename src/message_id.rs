@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// The id the server assigns to a published message, e.g. to poll for it or for the messages
+/// published after it, see `PublishResponse::message_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[serde(transparent)]
+pub struct MessageId(String);
+
+impl MessageId {
+    /// Returns the id as string, e.g. `"sPs71M8A2T"`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for MessageId {
+    fn from(id: String) -> Self {
+        MessageId(id)
+    }
+}
+
+impl From<&str> for MessageId {
+    fn from(id: &str) -> Self {
+        MessageId(id.to_string())
+    }
+}
+
+impl From<&String> for MessageId {
+    fn from(id: &String) -> Self {
+        MessageId(id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_from_strings() {
+        let id = MessageId::from("sPs71M8A2T");
+
+        assert_eq!(id, MessageId::from(String::from("sPs71M8A2T")));
+        assert_eq!(id.as_str(), "sPs71M8A2T");
+        assert_eq!(id.to_string(), "sPs71M8A2T");
+        assert_eq!(
+            serde_json::from_str::<MessageId>(r#""sPs71M8A2T""#).unwrap(),
+            id
+        );
+    }
+}
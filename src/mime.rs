@@ -0,0 +1,64 @@
+/// Guesses the `Content-Type` of a file from the extension of its name, falling back to
+/// `application/octet-stream` for unknown extensions.
+pub(crate) fn guess(filename: &str) -> &'static str {
+    let ext = match filename.rsplit_once('.') {
+        Some((_, ext)) => ext.to_ascii_lowercase(),
+        None => return "application/octet-stream",
+    };
+
+    match ext.as_str() {
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        "yaml" | "yml" => "application/yaml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "apk" => "application/vnd.android.package-archive",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guesses_from_the_extension() {
+        assert_eq!(guess("backup.LOG"), "text/plain");
+        assert_eq!(guess("photo.tar.jpeg"), "image/jpeg");
+        assert_eq!(guess("archive.xyz"), "application/octet-stream");
+        assert_eq!(guess("README"), "application/octet-stream");
+    }
+
+    #[test]
+    fn recognizes_common_attachment_types() {
+        assert_eq!(guess("screenshot.png"), "image/png");
+        assert_eq!(guess("report.pdf"), "application/pdf");
+        assert_eq!(guess("notes.md"), "text/markdown");
+        assert_eq!(guess("logs.tar.gz"), "application/gzip");
+        assert_eq!(
+            guess("app-release.apk"),
+            "application/vnd.android.package-archive"
+        );
+        assert_eq!(guess("clip.webm"), "video/webm");
+        assert_eq!(guess("data.xyz"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,64 @@
+use futures_util::future::BoxFuture;
+
+use crate::{ntfy, Client, Message, NtfyError};
+
+/// Sends notifications, e.g. to code against a notification backend and replace it with a
+/// fake in tests.
+///
+/// The trait is object safe, so `Box<dyn Notifier>` works.
+pub trait Notifier: Send + Sync {
+    /// Sends `msg`, discarding the response of the backend.
+    fn notify(&self, msg: Message) -> BoxFuture<'_, Result<(), NtfyError>>;
+}
+
+/// Publishes the message via `ntfy`.
+impl Notifier for Client {
+    fn notify(&self, msg: Message) -> BoxFuture<'_, Result<(), NtfyError>> {
+        Box::pin(async move { ntfy(self, msg).await.map(|_| ()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    use crate::test_server::{response, TestServer, PUBLISHED};
+
+    /// Records the messages it's asked to send instead of sending them.
+    #[derive(Default)]
+    struct Fake(Mutex<Vec<String>>);
+
+    impl Notifier for Fake {
+        fn notify(&self, msg: Message) -> BoxFuture<'_, Result<(), NtfyError>> {
+            self.0.lock().unwrap().push(msg.to_string());
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    async fn report_backup(notifier: &dyn Notifier) -> Result<(), NtfyError> {
+        let msg = Message::builder("Backup finished")
+            .title("Backup")
+            .build()
+            .unwrap();
+        notifier.notify(msg).await
+    }
+
+    #[tokio::test]
+    async fn swaps_the_client_for_a_fake() {
+        let fake = Fake::default();
+        report_backup(&fake).await.unwrap();
+        assert_eq!(*fake.0.lock().unwrap(), ["[Backup] Backup finished"]);
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let notifiers: Vec<Box<dyn Notifier>> = vec![
+            Box::new(Client::new(&server.url, "test", "uuid").unwrap()),
+            Box::new(fake),
+        ];
+        for notifier in &notifiers {
+            report_backup(notifier.as_ref()).await.unwrap();
+        }
+        assert_eq!(server.requests()[0].header("Title"), Some("Backup"));
+    }
+}
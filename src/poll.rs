@@ -0,0 +1,387 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+
+use crate::{
+    authorize, ntfy, received, send, Client, Message, NtfyError, PublishResponse, ReceivedMessage,
+    Since, SubscribeFilter,
+};
+
+/// Fetches the messages currently cached for the topic of `cli` without keeping a connection
+/// open, see [polling for messages](https://docs.ntfy.sh/subscribe/api/#poll-for-messages).
+///
+/// Only messages matching `filter` are returned, including those of its further topics.
+pub async fn poll(
+    cli: &Client,
+    filter: &SubscribeFilter,
+) -> Result<Vec<ReceivedMessage>, NtfyError> {
+    poll_stream(cli, filter).await?.try_collect().await
+}
+
+/// The outcome of [`ntfy_idempotent`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Idempotent {
+    /// No message with the key was cached, so the notification was sent.
+    Sent(PublishResponse),
+
+    /// A message with the key was already cached, so nothing was sent.
+    Duplicate(ReceivedMessage),
+}
+
+/// Sends a notification unless a message tagged with the idempotency `key` is already cached on
+/// the server, e.g. to safely resend after an ambiguous failure.
+///
+/// The key is added to the tags of `msg`, so it's also shown by the apps. This is best-effort
+/// only: the check relies on the server's message cache, and two concurrent calls with the same
+/// key can still both send.
+pub async fn ntfy_idempotent(
+    cli: &Client,
+    mut msg: Message,
+    key: &str,
+) -> Result<Idempotent, NtfyError> {
+    let filter = SubscribeFilter::new().since(Since::All).tags(&[key]);
+    if let Some(existing) = poll(cli, &filter).await?.into_iter().next() {
+        return Ok(Idempotent::Duplicate(existing));
+    }
+
+    if !msg.tags.iter().any(|tag| tag == key) {
+        msg.tags.push(key.to_string());
+    }
+    Ok(Idempotent::Sent(ntfy(cli, msg).await?))
+}
+
+/// Polls like [`poll`], but returns a stream that parses the messages as they arrive instead of
+/// buffering the whole response, e.g. for `Since::All` on a busy topic.
+pub async fn poll_stream(
+    cli: &Client,
+    filter: &SubscribeFilter,
+) -> Result<impl Stream<Item = Result<ReceivedMessage, NtfyError>>, NtfyError> {
+    let mut url = filter.url(cli, "/json")?;
+    url.query_pairs_mut().append_pair("poll", "1");
+    let req = authorize(cli, cli.http_client.get(url))?;
+    let res = send(cli, req).await?;
+
+    Ok(parse_lines(res.bytes_stream()))
+}
+
+/// Parses a stream of newline-delimited JSON into received messages, lines may be split across
+/// chunks.
+pub(crate) fn parse_lines<S, B, E>(
+    bytes: S,
+) -> impl Stream<Item = Result<ReceivedMessage, NtfyError>>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Into<NtfyError>,
+{
+    let state = (bytes, Vec::new(), VecDeque::new(), false);
+    stream::unfold(
+        state,
+        |(mut bytes, mut line, mut pending, mut done)| async move {
+            loop {
+                if let Some(item) = pending.pop_front() {
+                    return Some((item, (bytes, line, pending, done)));
+                }
+                if done {
+                    return None;
+                }
+
+                match bytes.next().await {
+                    Some(Ok(chunk)) => {
+                        for &byte in chunk.as_ref() {
+                            if byte == b'\n' {
+                                pending.extend(decode_line(&std::mem::take(&mut line)));
+                            } else {
+                                line.push(byte);
+                            }
+                        }
+                    }
+                    Some(Err(e)) => return Some((Err(e.into()), (bytes, line, pending, done))),
+                    None => {
+                        // the last line may not be terminated
+                        pending.extend(decode_line(&std::mem::take(&mut line)));
+                        done = true;
+                    }
+                }
+            }
+        },
+    )
+}
+
+fn decode_line(line: &[u8]) -> Option<Result<ReceivedMessage, NtfyError>> {
+    let line = String::from_utf8_lossy(line);
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    received::decode(&line)
+}
+
+/// Sends a notification and polls for it until the server returns it from its cache, which
+/// confirms that it was actually stored.
+///
+/// Fails with `NtfyError::Unconfirmed` if the message can't be polled within `timeout`. Requires
+/// caching to be enabled for the message.
+pub async fn ntfy_confirmed(
+    cli: &Client,
+    msg: Message,
+    timeout: Duration,
+) -> Result<ReceivedMessage, NtfyError> {
+    let id = ntfy(cli, msg).await?.id;
+    let filter = SubscribeFilter::new().id(&id);
+
+    let confirm = async {
+        loop {
+            if let Some(msg) = poll(cli, &filter).await?.into_iter().find(|m| m.id == id) {
+                return Ok(msg);
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    };
+    match tokio::time::timeout(timeout, confirm).await {
+        Ok(res) => res,
+        Err(_) => Err(NtfyError::Unconfirmed { id }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `chunks` as if they arrived one after another.
+    async fn decode_chunks(chunks: &[&'static str]) -> Result<Vec<ReceivedMessage>, NtfyError> {
+        let chunks = chunks
+            .iter()
+            .map(|chunk| Ok::<_, NtfyError>(chunk.as_bytes()));
+        parse_lines(stream::iter(chunks)).try_collect().await
+    }
+
+    #[tokio::test]
+    async fn decodes_multi_line_bodies() {
+        let body = concat!(
+            r#"{"id":"a","time":1,"event":"message","topic":"t","message":"first"}"#,
+            "\n\n",
+            r#"{"id":"b","time":2,"event":"keepalive","topic":"t"}"#,
+            "\n",
+            r#"{"id":"c","time":3,"event":"message","topic":"t","message":"second"}"#,
+            "\n",
+        );
+        let messages = decode_chunks(&[body]).await.unwrap();
+
+        let texts: Vec<_> = messages.iter().map(|m| m.message.as_deref()).collect();
+        assert_eq!(texts, [Some("first"), Some("second")]);
+    }
+
+    #[tokio::test]
+    async fn fails_on_malformed_lines() {
+        let body = "{\"id\":\"a\",\"time\":1,\"event\":\"message\",\"topic\":\"t\"}\nnot json\n";
+        assert!(matches!(
+            decode_chunks(&[body]).await,
+            Err(NtfyError::Decode(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn polls_with_auth() {
+        use crate::test_server::{response, TestServer};
+
+        let body = "{\"id\":\"a\",\"time\":1,\"event\":\"message\",\"topic\":\"t\"}\n";
+        let server = TestServer::start(vec![response(200, &[], body)]).await;
+        let cli = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+
+        let filter = SubscribeFilter::new().since(crate::Since::All);
+        let messages = poll(&cli, &filter).await.unwrap();
+
+        assert_eq!(messages.len(), 1);
+        let req = &server.requests()[0];
+        assert_eq!(req.path, "/test_uuid/json?since=all&poll=1");
+        assert_eq!(req.header("Authorization"), Some("Bearer tk_secret"));
+    }
+
+    #[tokio::test]
+    async fn merges_messages_of_several_topics() {
+        use crate::test_server::{response, TestServer};
+
+        let body = concat!(
+            r#"{"id":"a","time":1,"event":"message","topic":"test_uuid","message":"first"}"#,
+            "\n",
+            r#"{"id":"b","time":2,"event":"message","topic":"deploys","message":"second"}"#,
+            "\n",
+        );
+        let server = TestServer::start(vec![response(200, &[], body)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let filter = SubscribeFilter::new().topics(&["deploys"]);
+        let messages = poll(&cli, &filter).await.unwrap();
+
+        let deploys: Vec<_> = messages
+            .iter()
+            .filter(|msg| msg.topic == "deploys")
+            .map(|msg| msg.id.as_str())
+            .collect();
+        assert_eq!(deploys, ["b"]);
+        assert_eq!(server.requests()[0].path, "/test_uuid,deploys/json?poll=1");
+    }
+
+    #[tokio::test]
+    async fn confirms_published_messages() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+
+        let server = TestServer::start(vec![
+            response(200, &[], PUBLISHED),
+            response(200, &[], ""),
+            response(200, &[], &format!("{PUBLISHED}\n")),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi").build().unwrap();
+        let confirmed = ntfy_confirmed(&cli, msg, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(confirmed.id, "sPs71M8A2T");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].method, "POST");
+        for req in &requests[1..] {
+            assert_eq!(req.method, "GET");
+            assert_eq!(req.path, "/test_uuid/json?id=sPs71M8A2T&poll=1");
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_if_the_message_never_shows_up() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+
+        let server =
+            TestServer::start(vec![response(200, &[], PUBLISHED), response(200, &[], "")]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi").build().unwrap();
+        let res = ntfy_confirmed(&cli, msg, Duration::from_millis(100)).await;
+
+        assert!(matches!(res, Err(NtfyError::Unconfirmed { id }) if id == "sPs71M8A2T"));
+    }
+
+    #[tokio::test]
+    async fn decodes_lines_split_across_chunks() {
+        let messages = decode_chunks(&[
+            r#"{"id":"a","time":1,"event":"mess"#,
+            r#"age","topic":"t","message":"first"}"#,
+            "\n{\"id\":\"b\",\"time\":2,\"event\":\"message\",\"topic\":\"t\",\"message\":\"\u{1F600}",
+            "\"}",
+        ])
+        .await
+        .unwrap();
+
+        let texts: Vec<_> = messages.iter().map(|m| m.message.as_deref()).collect();
+        assert_eq!(texts, [Some("first"), Some("\u{1F600}")]);
+    }
+
+    #[tokio::test]
+    async fn decodes_multi_byte_characters_split_across_chunks() {
+        let line = "{\"id\":\"a\",\"time\":1,\"event\":\"message\",\"topic\":\"t\",\"message\":\"\u{1F600}\"}\n";
+        let (head, tail) = line
+            .as_bytes()
+            .split_at(line.find('\u{1F600}').unwrap() + 2);
+        let chunks = [head, tail].map(Ok::<_, NtfyError>);
+
+        let messages: Vec<_> = parse_lines(stream::iter(chunks))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(messages[0].message.as_deref(), Some("\u{1F600}"));
+    }
+
+    #[tokio::test]
+    async fn streams_polled_messages() {
+        use crate::test_server::{response, TestServer};
+
+        let body = concat!(
+            r#"{"id":"a","time":1,"event":"message","topic":"t","message":"first"}"#,
+            "\n",
+            r#"{"id":"b","time":2,"event":"message","topic":"t","message":"second"}"#,
+            "\n",
+        );
+        let server = TestServer::start(vec![response(200, &[], body)]).await;
+        let cli = Client::new(&server.url, "t", "").unwrap();
+
+        let stream = poll_stream(&cli, &SubscribeFilter::new()).await.unwrap();
+        let ids: Vec<_> = stream.map(|m| m.unwrap().id).collect().await;
+        assert_eq!(ids, ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn skips_sending_if_the_key_is_cached() {
+        use crate::test_server::{response, TestServer};
+
+        let cached = r#"{"id":"a","time":1,"event":"message","topic":"test_uuid","message":"hi","tags":["job-42"]}"#;
+        let server = TestServer::start(vec![response(200, &[], cached)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi").build().unwrap();
+        let res = ntfy_idempotent(&cli, msg, "job-42").await.unwrap();
+
+        assert!(matches!(res, Idempotent::Duplicate(existing) if existing.id == "a"));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(
+            requests[0].path,
+            "/test_uuid/json?since=all&tags=job-42&poll=1"
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_tagged_with_the_key_if_not_cached() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+
+        let server =
+            TestServer::start(vec![response(200, &[], ""), response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi").tags("backup").build().unwrap();
+        let res = ntfy_idempotent(&cli, msg, "job-42").await.unwrap();
+
+        assert!(matches!(res, Idempotent::Sent(res) if res.id == "sPs71M8A2T"));
+        let requests = server.requests();
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[1].header("Tags"), Some("backup,job-42"));
+    }
+
+    #[tokio::test]
+    async fn polls_for_published_messages_by_their_id() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+        use crate::{ntfy, Message, Since};
+
+        let polled = "{\"id\":\"sPs71M8A2T\",\"time\":1,\"event\":\"message\",\"topic\":\"t\"}\n";
+        let server = TestServer::start(vec![
+            response(200, &[], PUBLISHED),
+            response(200, &[], polled),
+            response(200, &[], ""),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.message_id().as_str(), "sPs71M8A2T");
+        let messages = poll(&cli, &SubscribeFilter::new().published(&res))
+            .await
+            .unwrap();
+        assert_eq!(messages[0].message_id(), res.message_id());
+        poll(&cli, &SubscribeFilter::new().since(Since::from(&res)))
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[1].path,
+            "/test_uuid/json?since=all&id=sPs71M8A2T&poll=1"
+        );
+        assert_eq!(requests[2].path, "/test_uuid/json?since=sPs71M8A2T&poll=1");
+    }
+}
@@ -0,0 +1,194 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+/// The priority of a notification, see [message priority](https://docs.ntfy.sh/publish/#message-priority).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Priority {
+    /// No vibration or sound, the notification will be under the fold in "Other notifications".
+    Min = 1,
+
+    /// No vibration or sound, the notification will not visibly show up until the drawer is pulled down.
+    Low = 2,
+
+    /// Short default vibration and sound, default notification behavior.
+    #[default]
+    Default = 3,
+
+    /// Long vibration burst, default notification sound with a pop-over notification.
+    High = 4,
+
+    /// Really long vibration bursts, default notification sound with a pop-over notification.
+    Max = 5,
+}
+
+impl Priority {
+    /// Returns the integer value (1-5) ntfy uses for this priority.
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Maps a `log` level to the priority of a notification about it: `Error` to `Max`, `Warn`
+    /// to `High`, `Info` to `Default` and `Debug` and `Trace` to `Low`.
+    #[cfg(feature = "log")]
+    pub fn from_log_level(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => Priority::Max,
+            log::Level::Warn => Priority::High,
+            log::Level::Info => Priority::Default,
+            log::Level::Debug | log::Level::Trace => Priority::Low,
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Priority::Min => "min",
+            Priority::Low => "low",
+            Priority::Default => "default",
+            Priority::High => "high",
+            Priority::Max => "max",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The error returned when a `Priority` can't be parsed from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePriorityError(String);
+
+impl fmt::Display for ParsePriorityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid priority `{}`, expected 1-5 or one of min, low, default, high, max, urgent",
+            self.0
+        )
+    }
+}
+
+impl core::error::Error for ParsePriorityError {}
+
+impl FromStr for Priority {
+    type Err = ParsePriorityError;
+
+    /// Parses a priority from its integer value (`"1"` to `"5"`) or one of the textual aliases
+    /// ntfy accepts (`min`, `low`, `default`, `high`, `max`/`urgent`), case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "1" | "min" => Ok(Priority::Min),
+            "2" | "low" => Ok(Priority::Low),
+            "3" | "default" => Ok(Priority::Default),
+            "4" | "high" => Ok(Priority::High),
+            "5" | "max" | "urgent" => Ok(Priority::Max),
+            _ => Err(ParsePriorityError(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<u8> for Priority {
+    type Error = ParsePriorityError;
+
+    /// Converts the integer value ntfy uses (1-5) into a priority, anything else is rejected.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Priority::Min),
+            2 => Ok(Priority::Low),
+            3 => Ok(Priority::Default),
+            4 => Ok(Priority::High),
+            5 => Ok(Priority::Max),
+            _ => Err(ParsePriorityError(value.to_string())),
+        }
+    }
+}
+
+impl TryFrom<i64> for Priority {
+    type Error = ParsePriorityError;
+
+    /// Converts the integer value ntfy uses (1-5) into a priority, anything else is rejected.
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        u8::try_from(value)
+            .map_err(|_| ParsePriorityError(value.to_string()))
+            .and_then(Priority::try_from)
+    }
+}
+
+/// Maps a `log` level to a priority, see `Priority::from_log_level`.
+#[cfg(feature = "log")]
+impl From<log::Level> for Priority {
+    fn from(level: log::Level) -> Self {
+        Priority::from_log_level(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_integers_and_aliases() {
+        assert_eq!("1".parse(), Ok(Priority::Min));
+        assert_eq!("low".parse(), Ok(Priority::Low));
+        assert_eq!("Default".parse(), Ok(Priority::Default));
+        assert_eq!(" high ".parse(), Ok(Priority::High));
+        assert_eq!("urgent".parse(), Ok(Priority::Max));
+        assert_eq!("5".parse(), Ok(Priority::Max));
+    }
+
+    #[test]
+    fn rejects_out_of_range_integers() {
+        assert!("0".parse::<Priority>().is_err());
+        assert!("6".parse::<Priority>().is_err());
+        assert!("loud".parse::<Priority>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for p in [
+            Priority::Min,
+            Priority::Low,
+            Priority::Default,
+            Priority::High,
+            Priority::Max,
+        ] {
+            assert_eq!(p.to_string().parse(), Ok(p));
+        }
+        assert_eq!(Priority::High.as_u8(), 4);
+    }
+
+    #[test]
+    fn converts_integers_in_range() {
+        assert_eq!(Priority::try_from(1u8), Ok(Priority::Min));
+        assert_eq!(Priority::try_from(5u8), Ok(Priority::Max));
+        assert_eq!(Priority::try_from(1i64), Ok(Priority::Min));
+        assert_eq!(Priority::try_from(5i64), Ok(Priority::Max));
+    }
+
+    #[test]
+    fn rejects_integers_out_of_range() {
+        for value in [0u8, 6, u8::MAX] {
+            assert!(Priority::try_from(value).is_err(), "{value}");
+        }
+        for value in [0i64, 6, -1, 257, i64::MIN] {
+            assert!(Priority::try_from(value).is_err(), "{value}");
+        }
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn maps_log_levels_to_priorities() {
+        for (level, priority) in [
+            (log::Level::Error, Priority::Max),
+            (log::Level::Warn, Priority::High),
+            (log::Level::Info, Priority::Default),
+            (log::Level::Debug, Priority::Low),
+            (log::Level::Trace, Priority::Low),
+        ] {
+            assert_eq!(Priority::from_log_level(level), priority, "{level}");
+            assert_eq!(Priority::from(level), priority, "{level}");
+        }
+    }
+}
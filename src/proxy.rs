@@ -0,0 +1,137 @@
+use std::fmt;
+
+use crate::NtfyError;
+
+/// A proxy requests to the ntfy server are sent through.
+///
+/// The `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` environment variables are only honored if no
+/// proxy is configured, setting any proxy disables them for all requests.
+#[derive(Clone)]
+pub struct Proxy {
+    /// Which requests are sent through the proxy.
+    scope: Scope,
+
+    /// The URL of the proxy, e.g. `"http://proxy.example.com:8080"`.
+    url: String,
+
+    /// An optional username and password to authenticate with the proxy.
+    basic_auth: Option<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    Http,
+    Https,
+    All,
+}
+
+impl Proxy {
+    /// Constructs a new `Proxy` for plain HTTP requests.
+    pub fn http(url: &str) -> Self {
+        Self::new(Scope::Http, url)
+    }
+
+    /// Constructs a new `Proxy` for HTTPS requests.
+    pub fn https(url: &str) -> Self {
+        Self::new(Scope::Https, url)
+    }
+
+    /// Constructs a new `Proxy` for all requests.
+    pub fn all(url: &str) -> Self {
+        Self::new(Scope::All, url)
+    }
+
+    fn new(scope: Scope, url: &str) -> Self {
+        Self {
+            scope,
+            url: url.to_string(),
+            basic_auth: None,
+        }
+    }
+
+    /// Authenticates with the proxy using a username and password.
+    pub fn basic_auth(self, username: &str, password: &str) -> Self {
+        Self {
+            basic_auth: Some((username.to_string(), password.to_string())),
+            ..self
+        }
+    }
+
+    /// Converts the proxy into its `reqwest` equivalent.
+    pub(crate) fn to_reqwest(&self) -> Result<reqwest::Proxy, NtfyError> {
+        let proxy = match self.scope {
+            Scope::Http => reqwest::Proxy::http(&self.url)?,
+            Scope::Https => reqwest::Proxy::https(&self.url)?,
+            Scope::All => reqwest::Proxy::all(&self.url)?,
+        };
+
+        Ok(match &self.basic_auth {
+            Some((user, pass)) => proxy.basic_auth(user, pass),
+            None => proxy,
+        })
+    }
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proxy")
+            .field("scope", &self.scope)
+            .field("url", &self.url)
+            .field(
+                "basic_auth",
+                &self.basic_auth.as_ref().map(|(user, _)| (user, "***")),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_server::{response, TestServer, PUBLISHED};
+    use crate::{ntfy, Client, Message};
+
+    #[tokio::test]
+    async fn sends_requests_through_the_proxy() {
+        let proxy = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server("http://ntfy.invalid")
+            .topic("test")
+            .uuid("uuid")
+            .allow_insecure(true)
+            .proxy(Proxy::http(&proxy.url).basic_auth("Aladdin", "open sesame"))
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        let req = &proxy.requests()[0];
+        assert_eq!(req.path, "http://ntfy.invalid/test_uuid");
+        assert_eq!(
+            req.header("Proxy-Authorization"),
+            Some("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_proxy_urls() {
+        let res = Client::builder()
+            .server("https://ntfy.sh")
+            .topic("test")
+            .uuid("uuid")
+            .proxy(Proxy::all("not a url"))
+            .build();
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn redacts_the_password_in_debug_output() {
+        let proxy = Proxy::https("http://proxy:8080").basic_auth("user", "hunter2");
+
+        assert!(!format!("{proxy:?}").contains("hunter2"));
+    }
+}
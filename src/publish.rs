@@ -0,0 +1,170 @@
+use reqwest::header::HeaderMap;
+
+use crate::{Attempts, MessageId, NtfyError};
+
+/// The message stored by the server for a successfully published notification.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct PublishResponse {
+    /// The unique id assigned by the server, e.g. to poll for the message later.
+    pub id: String,
+
+    /// The Unix timestamp at which the message was published.
+    pub time: i64,
+
+    /// The Unix timestamp at which the message is deleted from the server cache, if cached.
+    #[serde(default)]
+    pub expires: Option<i64>,
+
+    /// The event type, `message` for published notifications.
+    pub event: String,
+
+    /// The topic the message was published to.
+    pub topic: String,
+
+    /// The text of the message, if any.
+    #[serde(default)]
+    pub message: Option<String>,
+
+    /// The number of attempts it took to deliver the notification.
+    #[serde(skip, default = "first_attempt")]
+    pub attempts: u32,
+
+    /// The raw value of the `X-Message-Id` header, if sent, e.g. by a proxy in front of ntfy.
+    /// Not necessarily the `id` of the message, see `message_id`.
+    #[serde(skip)]
+    pub header_message_id: Option<String>,
+
+    /// The number of requests left before being rate limited, from the `X-RateLimit-Remaining`
+    /// header if sent by the server.
+    #[serde(skip)]
+    pub rate_limit_remaining: Option<u64>,
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+impl PublishResponse {
+    /// Returns the `id` of the message, e.g. for `SubscribeFilter::id` or `Since::Id`.
+    pub fn message_id(&self) -> MessageId {
+        MessageId::from(&self.id)
+    }
+
+    /// Parses the body of a successful async response.
+    pub(crate) async fn from_response(res: reqwest::Response) -> Result<Self, NtfyError> {
+        let attempts = res.extensions().get::<Attempts>().map_or(1, |a| a.0);
+        let headers = res.headers().clone();
+        let body = res.text().await?;
+        Self::from_parts(&headers, &body, attempts)
+    }
+
+    /// Parses the headers and JSON body the server returns for a published message.
+    pub(crate) fn from_parts(
+        headers: &HeaderMap,
+        body: &str,
+        attempts: u32,
+    ) -> Result<Self, NtfyError> {
+        let header = |name| headers.get(name)?.to_str().ok().map(str::trim);
+        Ok(Self {
+            attempts,
+            header_message_id: header("X-Message-Id").map(str::to_string),
+            rate_limit_remaining: header("X-RateLimit-Remaining").and_then(|v| v.parse().ok()),
+            ..serde_json::from_str(body)?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_publish_responses() {
+        let body = r#"{"id":"sPs71M8A2T","time":1673542291,"expires":1673585491,"event":"message","topic":"mytopic","message":"Backup successful 😀"}"#;
+        let res = PublishResponse::from_parts(&HeaderMap::new(), body, 2).unwrap();
+
+        assert_eq!(
+            res,
+            PublishResponse {
+                id: "sPs71M8A2T".into(),
+                time: 1673542291,
+                expires: Some(1673585491),
+                event: "message".into(),
+                topic: "mytopic".into(),
+                message: Some("Backup successful 😀".into()),
+                attempts: 2,
+                header_message_id: None,
+                rate_limit_remaining: None,
+            }
+        );
+    }
+
+    #[test]
+    fn accepts_uncached_messages_without_text() {
+        let body = r#"{"id":"abc","time":1,"event":"message","topic":"t"}"#;
+        let res = PublishResponse::from_parts(&HeaderMap::new(), body, 1).unwrap();
+
+        assert_eq!(res.expires, None);
+        assert_eq!(res.message, None);
+    }
+
+    #[test]
+    fn rejects_malformed_bodies() {
+        let res = PublishResponse::from_parts(&HeaderMap::new(), "ok", 1);
+
+        assert!(matches!(res, Err(NtfyError::Decode(_))));
+    }
+
+    #[tokio::test]
+    async fn returns_the_attempts_of_raw_responses() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+        use crate::{ntfy, ntfy_raw, Client, Message, RetryPolicy};
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .retry(RetryPolicy::new(3))
+            .build()
+            .unwrap();
+
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        let raw = ntfy_raw(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.id, "sPs71M8A2T");
+        assert_eq!(res.attempts, 1);
+        assert_eq!(raw.extensions().get::<Attempts>(), Some(&Attempts(1)));
+    }
+
+    #[tokio::test]
+    async fn parses_rate_limit_headers() {
+        use crate::test_server::{response, TestServer, PUBLISHED};
+        use crate::{ntfy, Client, Message};
+
+        let server = TestServer::start(vec![
+            response(
+                200,
+                &[
+                    ("X-Message-Id", "sPs71M8A2T"),
+                    ("X-RateLimit-Remaining", "41"),
+                ],
+                PUBLISHED,
+            ),
+            response(200, &[("X-RateLimit-Remaining", "many")], PUBLISHED),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let res = ntfy(&cli, Message::from("hi")).await.unwrap();
+        assert_eq!(res.header_message_id.as_deref(), Some("sPs71M8A2T"));
+        assert_eq!(res.rate_limit_remaining, Some(41));
+
+        let res = ntfy(&cli, Message::from("hi")).await.unwrap();
+        assert_eq!(res.header_message_id, None);
+        assert_eq!(res.rate_limit_remaining, None);
+    }
+}
@@ -0,0 +1,1979 @@
+use std::io::Write;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+
+use async_compression::tokio::bufread::GzipEncoder;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::{stream, StreamExt};
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
+
+use crate::{
+    custom_headers, escape_newlines, header, headers, json, mime, redirect, retry, validate,
+    Client, Message, NtfyError, PublishResponse, Redirect, RequestContext, ToMessage, Topic,
+};
+
+/// Sends a notifications and returns the message stored by the server.
+///
+/// Responses with a non-success status code are returned as `NtfyError::Server`. If the `Client`
+/// has a `RetryPolicy`, transient failures are retried.
+pub async fn ntfy(cli: &Client, msg: Message) -> Result<PublishResponse, NtfyError> {
+    PublishResponse::from_response(ntfy_raw(cli, msg).await?).await
+}
+
+/// Sends the notification for `event` like [`ntfy`], see `ToMessage`.
+pub async fn ntfy_event(
+    cli: &Client,
+    event: &impl ToMessage,
+) -> Result<PublishResponse, NtfyError> {
+    ntfy(cli, event.to_message()).await
+}
+
+/// Sends a notifications like [`ntfy`] but returns the raw response of the publish request.
+///
+/// The number of attempts is stored as `Attempts` in the extensions of the response.
+pub async fn ntfy_raw(cli: &Client, mut msg: Message) -> Result<reqwest::Response, NtfyError> {
+    apply_defaults(cli, &mut msg);
+    check_size(cli, &msg)?;
+
+    let mut req = request(cli, cli.publish_method.clone(), &msg)?;
+    if let Some(content_type) = &msg.content_type {
+        req = req.header(reqwest::header::CONTENT_TYPE, content_type);
+    }
+    let (body, encoding) = encode_body(cli, msg.message.into_bytes())?;
+    if let Some(encoding) = encoding {
+        req = req.header(reqwest::header::CONTENT_ENCODING, encoding);
+    }
+
+    retry::send(cli, req.body(body)).await
+}
+
+/// Sends multiple notifications concurrently, with at most `concurrency` requests in flight.
+///
+/// Returns the result of every notification in the order of `msgs`, a failed notification
+/// doesn't stop the others from being sent.
+pub async fn ntfy_many(
+    cli: &Client,
+    msgs: Vec<Message>,
+    concurrency: usize,
+) -> Vec<Result<PublishResponse, NtfyError>> {
+    ntfy_many_timeout(cli, msgs, concurrency, None, None).await
+}
+
+/// Sends multiple notifications like [`ntfy_many`], but fails every notification that takes
+/// longer than `item_timeout` or isn't done by the time `deadline` has passed since the call.
+///
+/// Notifications that hit their own timeout fail with `NtfyError::Elapsed`, those cut off by
+/// the overall deadline with `NtfyError::Deadline`, without holding up the others.
+pub async fn ntfy_many_timeout(
+    cli: &Client,
+    msgs: Vec<Message>,
+    concurrency: usize,
+    item_timeout: Option<Duration>,
+    deadline: Option<Duration>,
+) -> Vec<Result<PublishResponse, NtfyError>> {
+    let deadline = deadline.map(|deadline| (deadline, tokio::time::Instant::now() + deadline));
+    let mut results: Vec<_> = stream::iter(msgs.into_iter().enumerate())
+        .map(|(i, msg)| async move {
+            let send = async {
+                match item_timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, ntfy(cli, msg))
+                        .await
+                        .unwrap_or(Err(NtfyError::Elapsed { timeout })),
+                    None => ntfy(cli, msg).await,
+                }
+            };
+            let res = match deadline {
+                Some((deadline, at)) => tokio::time::timeout_at(at, send)
+                    .await
+                    .unwrap_or(Err(NtfyError::Deadline { deadline })),
+                None => send.await,
+            };
+            (i, res)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, res)| res).collect()
+}
+
+/// Sends a notification using ntfy's [JSON publishing](https://docs.ntfy.sh/publish/#publish-as-json)
+/// and returns the message stored by the server.
+///
+/// All fields are sent as JSON body to the server root instead of as headers, which avoids
+/// encoding non-ASCII text into headers altogether. Custom headers are still sent as headers.
+pub async fn ntfy_json(cli: &Client, mut msg: Message) -> Result<PublishResponse, NtfyError> {
+    apply_defaults(cli, &mut msg);
+    check_size(cli, &msg)?;
+
+    let body = json::body(cli, &msg)?;
+    let url = api_url(server(cli, &msg), cli, "")?;
+    let mut req = authorize(cli, cli.http_client.post(url))?
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    if !msg.cache {
+        req = req.header(header::name(cli, "Cache"), "no");
+    }
+    if !msg.firebase {
+        req = req.header(header::name(cli, "Firebase"), "no");
+    }
+    for (name, value) in custom_headers(&msg)? {
+        req = req.header(name, value);
+    }
+    if let Some(timeout) = msg.timeout {
+        req = req.timeout(timeout);
+    }
+
+    PublishResponse::from_response(retry::send(cli, req.body(body)).await?).await
+}
+
+/// Publishes each message to its topic on the server of `cli` like [`ntfy_json`] and returns
+/// the result of every message in order.
+///
+/// The messages are sent one after another, so they reuse a single keep-alive connection
+/// instead of opening one per topic. A failed message doesn't stop the others from being sent.
+pub async fn publish_batch(
+    cli: &Client,
+    items: Vec<(Topic, Message)>,
+) -> Vec<Result<PublishResponse, NtfyError>> {
+    let mut results = Vec::with_capacity(items.len());
+    for (topic, msg) in items {
+        let cli = Client {
+            topic,
+            ..cli.clone()
+        };
+        results.push(ntfy_json(&cli, msg).await);
+    }
+
+    results
+}
+
+/// Uploads a local file as attachment and returns the message stored by the server.
+///
+/// The file name is used as `Filename` and the `Content-Type` is guessed from its extension. The
+/// text of the message becomes the caption of the notification and is sent as `Message` header,
+/// as the body holds the file. Captions larger than `ClientBuilder::max_message_bytes` are
+/// rejected before sending.
+pub async fn ntfy_file(
+    cli: &Client,
+    mut msg: Message,
+    path: impl AsRef<Path>,
+) -> Result<PublishResponse, NtfyError> {
+    apply_defaults(cli, &mut msg);
+    let path = path.as_ref();
+    check_upload(&msg)?;
+    check_size(cli, &msg)?;
+
+    let bytes = tokio::fs::read(path).await.map_err(|e| {
+        NtfyError::InvalidInput(format!("could not read file {}: {e}", path.display()))
+    })?;
+    let filename = upload_filename(path)?;
+
+    let mut req = upload(cli, &msg, &filename)?;
+    let (body, encoding) = encode_body(cli, bytes)?;
+    if let Some(encoding) = encoding {
+        req = req.header(reqwest::header::CONTENT_ENCODING, encoding);
+    }
+
+    PublishResponse::from_response(retry::send(cli, req.body(body)).await?).await
+}
+
+/// Uploads a local file as attachment like [`ntfy_file`], but streams it from disk instead of
+/// reading it into memory first.
+///
+/// The size of the file is sent as `Content-Length` so the server can reject attachments that
+/// are too large upfront. Files whose size isn't known upfront, e.g. named pipes, are sent with
+/// chunked encoding instead. Like all streamed uploads, it's never retried.
+pub async fn ntfy_file_streamed(
+    cli: &Client,
+    msg: Message,
+    path: impl AsRef<Path>,
+) -> Result<PublishResponse, NtfyError> {
+    let path = path.as_ref();
+    let read_error =
+        |e| NtfyError::InvalidInput(format!("could not read file {}: {e}", path.display()));
+    let file = tokio::fs::File::open(path).await.map_err(read_error)?;
+    let metadata = file.metadata().await.map_err(read_error)?;
+    let content_length = metadata.is_file().then_some(metadata.len());
+    let filename = upload_filename(path)?;
+
+    ntfy_stream(cli, msg, file, &filename, content_length).await
+}
+
+/// Returns the `Filename` of an upload of `path`, i.e. its file name.
+fn upload_filename(path: &Path) -> Result<String, NtfyError> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| NtfyError::InvalidInput(format!("{} is not a file", path.display())))
+}
+
+/// Uploads an attachment read from `reader` without buffering it in memory and returns the
+/// message stored by the server.
+///
+/// The caption is sent as `Message` header like with [`ntfy_file`]. The `Content-Type` is
+/// guessed from `filename`. If `content_length` is given, it's sent as `Content-Length` so the
+/// server can reject attachments that are too large upfront. Streamed uploads are never retried.
+///
+/// If a gzip threshold is configured via `ClientBuilder::gzip_threshold`, attachments larger
+/// than it or of unknown length are compressed on the fly and sent without `Content-Length`.
+pub async fn ntfy_stream<R>(
+    cli: &Client,
+    mut msg: Message,
+    reader: R,
+    filename: &str,
+    content_length: Option<u64>,
+) -> Result<PublishResponse, NtfyError>
+where
+    R: AsyncRead + Send + 'static,
+{
+    apply_defaults(cli, &mut msg);
+    check_upload(&msg)?;
+    check_size(cli, &msg)?;
+
+    let compress = cli
+        .gzip_threshold
+        .is_some_and(|threshold| content_length.is_none_or(|len| len > threshold as u64));
+    let reader: Pin<Box<dyn AsyncRead + Send>> = if compress {
+        Box::pin(GzipEncoder::new(BufReader::new(reader)))
+    } else {
+        Box::pin(reader)
+    };
+
+    let chunks = stream::unfold(reader, |mut reader| async move {
+        let mut chunk = vec![0; 8192];
+        match reader.read(&mut chunk).await {
+            Ok(0) => None,
+            Ok(n) => {
+                chunk.truncate(n);
+                Some((Ok(chunk), reader))
+            }
+            Err(e) => Some((Err(e), reader)),
+        }
+    });
+
+    let mut req = upload(cli, &msg, filename)?;
+    match content_length {
+        _ if compress => req = req.header(reqwest::header::CONTENT_ENCODING, "gzip"),
+        Some(len) => req = req.header(reqwest::header::CONTENT_LENGTH, len),
+        None => {}
+    }
+    let req = req.body(reqwest::Body::wrap_stream(chunks));
+
+    PublishResponse::from_response(retry::send(cli, req).await?).await
+}
+
+/// Publishes `body` as opaque [UnifiedPush](https://unifiedpush.org) message, i.e. to the topic
+/// with `?up=1`, and returns the message stored by the server.
+///
+/// The body is sent as is without any ntfy headers, e.g. for self-hosted push distributors.
+pub async fn ntfy_up(cli: &Client, body: impl Into<Vec<u8>>) -> Result<PublishResponse, NtfyError> {
+    let body = body.into();
+    if body.len() > cli.max_message_bytes {
+        return Err(NtfyError::InvalidInput(format!(
+            "message is {} bytes, at most {} bytes are allowed",
+            body.len(),
+            cli.max_message_bytes
+        )));
+    }
+
+    let mut url = topic_url(cli, "")?;
+    url.query_pairs_mut().append_pair("up", "1");
+    let req = authorize(cli, cli.http_client.post(url))?.body(body);
+
+    PublishResponse::from_response(retry::send(cli, req).await?).await
+}
+
+/// Rejects an upload combined with an attachment URL.
+fn check_upload(msg: &Message) -> Result<(), NtfyError> {
+    if msg.attach.is_some() {
+        return Err(NtfyError::InvalidInput(
+            "a file upload can't be combined with an attachment URL".into(),
+        ));
+    }
+    if msg.template {
+        return Err(NtfyError::InvalidInput(
+            "a file upload can't be combined with templating".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Prepares the PUT request uploading an attachment named `filename`, using the text of `msg`
+/// as caption.
+fn upload(
+    cli: &Client,
+    msg: &Message,
+    filename: &str,
+) -> Result<reqwest::RequestBuilder, NtfyError> {
+    let mut req = request(cli, reqwest::Method::PUT, msg)?
+        .header(
+            header::name(cli, "Filename"),
+            header::encode_value(filename),
+        )
+        .header(
+            "Content-Type",
+            msg.content_type.as_deref().unwrap_or(mime::guess(filename)),
+        );
+    if !msg.message.is_empty() {
+        req = req.header(
+            header::name(cli, "Message"),
+            header::encode_value(&escape_newlines(&msg.message)),
+        );
+    }
+
+    Ok(req)
+}
+
+/// Compresses `body` with gzip if it exceeds the threshold of `cli`, returning the body to send and
+/// its `Content-Encoding`.
+pub(crate) fn encode_body(
+    cli: &Client,
+    body: Vec<u8>,
+) -> Result<(Vec<u8>, Option<&'static str>), NtfyError> {
+    match cli.gzip_threshold {
+        Some(threshold) if body.len() > threshold => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder
+                .write_all(&body)
+                .and_then(|_| encoder.finish())
+                .map(|body| (body, Some("gzip")))
+                .map_err(|e| NtfyError::InvalidInput(format!("could not compress body: {e}")))
+        }
+        _ => Ok((body, None)),
+    }
+}
+
+/// Rejects a message body larger than the limit configured for `cli`.
+pub(crate) fn check_size(cli: &Client, msg: &Message) -> Result<(), NtfyError> {
+    if msg.message.len() > cli.max_message_bytes {
+        return Err(NtfyError::InvalidInput(format!(
+            "message is {} bytes, at most {} bytes are allowed",
+            msg.message.len(),
+            cli.max_message_bytes
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns the headers of `msg`, rejecting them if the `Actions`, `Click` and `Attach` headers
+/// exceed the limit configured for `cli`.
+pub(crate) fn checked_headers(
+    cli: &Client,
+    msg: &Message,
+) -> Result<Vec<(String, String)>, NtfyError> {
+    let headers = headers(msg)?;
+    let size: usize = headers
+        .iter()
+        .filter(|(name, _)| ["Actions", "Click", "Attach"].contains(&name.as_str()))
+        .map(|(name, value)| name.len() + value.len())
+        .sum();
+    if size > cli.max_header_bytes {
+        return Err(NtfyError::InvalidInput(format!(
+            "actions, click and attach headers are {size} bytes, at most {} bytes are allowed, \
+             consider `ntfy_json` instead",
+            cli.max_header_bytes
+        )));
+    }
+
+    Ok(headers)
+}
+
+/// Fills in the defaults configured on the client for fields the message doesn't set.
+pub(crate) fn apply_defaults(cli: &Client, msg: &mut Message) {
+    if msg.priority.is_none() && msg.use_default_priority {
+        msg.priority = cli.default_priority;
+    }
+    if msg.tags.is_empty() && msg.use_default_tags {
+        msg.tags = cli.default_tags.clone();
+    }
+    for (name, value) in &cli.default_headers {
+        let overridden = msg.headers.keys().any(|own| own.eq_ignore_ascii_case(name));
+        if !overridden {
+            msg.headers.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+/// Prepares a request to the topic of `cli` carrying all headers of `msg`, but no body.
+fn request(
+    cli: &Client,
+    method: reqwest::Method,
+    msg: &Message,
+) -> Result<reqwest::RequestBuilder, NtfyError> {
+    let mut req = authorize(cli, cli.http_client.request(method, publish_url(cli, msg)?))?;
+    for (name, value) in checked_headers(cli, msg)? {
+        req = req.header(header::name(cli, &name), value);
+    }
+    if let Some(timeout) = msg.timeout {
+        req = req.timeout(timeout);
+    }
+
+    Ok(req)
+}
+
+/// Returns the URL of the topic of `cli`, followed by `endpoint`, e.g. `"/sse"`.
+///
+/// The uuid is appended to the topic as `topic_uuid` unless it's empty.
+pub(crate) fn topic_url(cli: &Client, endpoint: &str) -> Result<reqwest::Url, NtfyError> {
+    topic_url_on(&cli.server, cli, endpoint)
+}
+
+/// Returns the URL `msg` is published to, i.e. the topic URL on its server override if set.
+pub(crate) fn publish_url(cli: &Client, msg: &Message) -> Result<reqwest::Url, NtfyError> {
+    topic_url_on(server(cli, msg), cli, "")
+}
+
+/// Returns the server `msg` is published to.
+pub(crate) fn server<'a>(cli: &'a Client, msg: &'a Message) -> &'a str {
+    msg.server.as_deref().unwrap_or(&cli.server)
+}
+
+/// Builds the URL of the topic of `cli` and `endpoint` on `server`.
+fn topic_url_on(server: &str, cli: &Client, endpoint: &str) -> Result<reqwest::Url, NtfyError> {
+    cli.topic.validate()?;
+    api_url(
+        server,
+        cli,
+        &format!("{}{endpoint}", cli.topic.path_segment()),
+    )
+}
+
+/// Builds the URL of `path` on `server`, below the API version of `cli` if one is pinned.
+///
+/// Rejects plain `http` URLs to public hosts unless `ClientBuilder::allow_insecure` is set, so
+/// credentials are never sent unencrypted to any endpoint.
+pub(crate) fn api_url(server: &str, cli: &Client, path: &str) -> Result<reqwest::Url, NtfyError> {
+    let server = server.trim_end_matches('/');
+    let url = match &cli.api_version {
+        Some(version) => format!("{server}/{version}/{path}"),
+        None => format!("{server}/{path}"),
+    };
+    let url = reqwest::Url::parse(&url)?;
+    validate::secure(&url, cli.allow_insecure)?;
+
+    Ok(url)
+}
+
+/// Returns the `Authorization` header for the configured token or basic auth credentials of `cli`.
+pub(crate) fn authorization(cli: &Client) -> Result<Option<HeaderValue>, NtfyError> {
+    let value = match (&cli.token, &cli.basic_auth) {
+        (Some(_), Some(_)) => {
+            return Err(NtfyError::InvalidInput(
+                "token and basic auth are mutually exclusive".into(),
+            ))
+        }
+        (Some(token), None) => format!("Bearer {token}"),
+        (None, Some((user, pass))) => {
+            format!("Basic {}", STANDARD.encode(format!("{user}:{pass}")))
+        }
+        (None, None) => return Ok(None),
+    };
+
+    let mut value = HeaderValue::from_str(&value)
+        .map_err(|_| NtfyError::InvalidInput("credentials contain invalid characters".into()))?;
+    value.set_sensitive(true);
+    Ok(Some(value))
+}
+
+/// Adds the configured token or basic auth credentials of `cli` to a request.
+pub(crate) fn authorize(
+    cli: &Client,
+    req: reqwest::RequestBuilder,
+) -> Result<reqwest::RequestBuilder, NtfyError> {
+    Ok(match authorization(cli)? {
+        Some(value) => req.header(AUTHORIZATION, value),
+        None => req,
+    })
+}
+
+/// Sends a prepared request, turning unsuccessful responses into `NtfyError::Server`.
+pub(crate) async fn send(
+    cli: &Client,
+    req: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, NtfyError> {
+    let req = req.build()?;
+    let context = RequestContext::new(cli, req.method(), req.url());
+    let res = execute(cli, req).await?;
+    if !res.status().is_success() {
+        return Err(NtfyError::from_response(res).await.with_context(&context));
+    }
+
+    Ok(res)
+}
+
+/// Sends a request via the transport of `cli` and follows redirects according to its `Redirect`
+/// policy, resending the request with the same method and body.
+pub(crate) async fn execute(
+    cli: &Client,
+    mut req: reqwest::Request,
+) -> Result<reqwest::Response, NtfyError> {
+    let mut followed = 0;
+    loop {
+        let resend = match cli.redirect {
+            Redirect::None => None,
+            _ => req.try_clone(),
+        };
+        let res = transmit(cli, req).await?;
+        let Some(mut next) = resend else {
+            return Ok(res);
+        };
+        let from = next.url().clone();
+        let Some(target) =
+            cli.redirect
+                .target(next.method(), &from, res.status(), res.headers(), followed)
+        else {
+            return Ok(res);
+        };
+
+        validate::secure(&target, cli.allow_insecure)?;
+        redirect::strip_credentials(next.headers_mut(), &from, &target);
+        *next.url_mut() = target;
+        req = next;
+        followed += 1;
+    }
+}
+
+/// Sends a single request via the transport of `cli`, logging its outcome with the `tracing`
+/// feature.
+async fn transmit(cli: &Client, req: reqwest::Request) -> Result<reqwest::Response, NtfyError> {
+    #[cfg(feature = "tracing")]
+    let (method, start) = (req.method().clone(), std::time::Instant::now());
+
+    let context = RequestContext::new(cli, req.method(), req.url());
+    let res = cli
+        .transport
+        .send(req)
+        .await
+        .map_err(|e| e.with_context(&context));
+
+    #[cfg(feature = "tracing")]
+    match &res {
+        Ok(res) => tracing::debug!(
+            %method,
+            status = res.status().as_u16(),
+            latency_ms = start.elapsed().as_millis() as u64,
+            "request completed"
+        ),
+        Err(e) => tracing::debug!(
+            %method,
+            error = %e,
+            latency_ms = start.elapsed().as_millis() as u64,
+            "request failed"
+        ),
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{response, TestServer, PUBLISHED};
+    use crate::{Action, Priority};
+
+    #[tokio::test]
+    async fn clones_reuse_the_connection_pool() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+        let clone = cli.clone();
+
+        ntfy(&cli, Message::builder("one").build().unwrap())
+            .await
+            .unwrap();
+        ntfy(&clone, Message::builder("two").build().unwrap())
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|req| req.method == "POST" && req.path == "/test_uuid"));
+        assert_eq!(requests[1].body, b"two");
+        assert_eq!(server.connections(), 1);
+    }
+
+    #[tokio::test]
+    async fn returns_rejected_notifications_as_server_errors() {
+        let body = r#"{"code":40301,"http":403,"error":"forbidden"}"#;
+        let server = TestServer::start(vec![response(403, &[], body)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let e = ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap_err();
+        match &e {
+            NtfyError::Server {
+                status,
+                error: Some(error),
+                body: raw,
+                ..
+            } => {
+                assert_eq!(*status, 403);
+                assert_eq!(error.code, 40301);
+                assert_eq!(error.error, "forbidden");
+                assert_eq!(raw, body);
+            }
+            e => panic!("unexpected error {e:?}"),
+        }
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "POST {}/test_*** (topic test): server responded with 403 (code 40301): forbidden",
+                server.url
+            )
+        );
+        assert!(!e.to_string().contains("uuid"));
+        assert_eq!(e.context().unwrap().topic, "test");
+    }
+
+    #[tokio::test]
+    async fn masks_the_uuid_in_the_context_of_request_errors() {
+        let cli = Client::new("http://127.0.0.1:9", "backup", "8fd2c6").unwrap();
+
+        let e = ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(e, NtfyError::Connect { .. }));
+        let context = e.context().unwrap();
+        assert_eq!(context.method, reqwest::Method::POST);
+        assert_eq!(context.url, "http://127.0.0.1:9/backup_***");
+        assert!(e
+            .to_string()
+            .starts_with("POST http://127.0.0.1:9/backup_*** (topic backup): "));
+        assert!(!e.to_string().contains("8fd2c6"));
+        assert!(!format!("{e:?}").contains("8fd2c6"));
+    }
+
+    #[tokio::test]
+    async fn returns_successful_responses_as_is() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let res = ntfy_raw(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text().await.unwrap(), PUBLISHED);
+    }
+
+    #[tokio::test]
+    async fn sends_the_token_only_if_configured() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let anonymous = Client::new(&server.url, "test", "uuid").unwrap();
+        let authenticated = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+
+        ntfy(&anonymous, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        ntfy(&authenticated, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Authorization"), None);
+        assert_eq!(
+            requests[1].header("Authorization"),
+            Some("Bearer tk_secret")
+        );
+    }
+
+    #[test]
+    fn redacts_the_token_in_debug_output() {
+        let cli = Client::with_token("https://ntfy.sh", "test", "uuid", "tk_secret").unwrap();
+        let debug = format!("{cli:?}");
+        assert!(!debug.contains("tk_secret"));
+        assert!(debug.contains("***"));
+    }
+
+    #[tokio::test]
+    async fn sends_basic_auth_credentials() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli =
+            Client::with_basic_auth(&server.url, "test", "uuid", "Aladdin", "open sesame").unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.requests()[0].header("Authorization"),
+            Some("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==")
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_token_combined_with_basic_auth() {
+        let cli = Client {
+            token: Some("tk_secret".into()),
+            ..Client::with_basic_auth("https://ntfy.sh", "test", "uuid", "user", "pass").unwrap()
+        };
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap()).await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn redacts_the_password_in_debug_output() {
+        let cli =
+            Client::with_basic_auth("https://ntfy.sh", "test", "uuid", "phil", "hunter2").unwrap();
+        let debug = format!("{cli:?}");
+        assert!(debug.contains("phil"));
+        assert!(!debug.contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn sends_https_and_intent_click_urls() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+        let intent =
+            "intent://scan/#Intent;scheme=zxing;package=com.google.zxing.client.android;end";
+
+        for url in ["https://example.com/backups", intent] {
+            let msg = Message::builder("hi").click(url).build().unwrap();
+            ntfy(&cli, msg).await.unwrap();
+        }
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].header("Click"),
+            Some("https://example.com/backups")
+        );
+        assert_eq!(requests[1].header("Click"), Some(intent));
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_click_urls() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let res = ntfy(
+            &cli,
+            Message::builder("hi").click("not a url").build_unchecked(),
+        )
+        .await;
+        assert!(matches!(res, Err(NtfyError::Url(_))));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sends_attachments_with_optional_filename() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+        let url = "https://example.com/flower.jpg";
+
+        ntfy(&cli, Message::builder("hi").attach(url).build().unwrap())
+            .await
+            .unwrap();
+        let msg = Message::builder("hi").attach(url).filename("rosé.jpg");
+        ntfy(&cli, msg.build().unwrap()).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Attach"), Some(url));
+        assert_eq!(requests[0].header("Filename"), None);
+        assert_eq!(requests[1].header("Attach"), Some(url));
+        assert_eq!(
+            requests[1].header("Filename"),
+            Some(header::encode_value("rosé.jpg").as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_filename_without_attachment() {
+        let cli = Client::new("https://ntfy.sh", "test", "uuid").unwrap();
+        let res = ntfy(
+            &cli,
+            Message::builder("hi").filename("a.jpg").build_unchecked(),
+        )
+        .await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+
+        let msg = Message::builder("hi").attach("not a url").build_unchecked();
+        assert!(matches!(ntfy(&cli, msg).await, Err(NtfyError::Url(_))));
+    }
+
+    #[tokio::test]
+    async fn uploads_files_with_the_caption_as_header() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+        let path = std::env::temp_dir().join(format!("ntfy-rs-upload-{}.txt", std::process::id()));
+        std::fs::write(&path, "backup finished").unwrap();
+
+        let res = ntfy_file(
+            &cli,
+            Message::builder("nightly log").build().unwrap(),
+            &path,
+        )
+        .await;
+        std::fs::remove_file(&path).unwrap();
+        res.unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.method, "PUT");
+        assert_eq!(req.body, b"backup finished");
+        assert_eq!(req.header("Filename"), path.file_name().unwrap().to_str());
+        assert_eq!(req.header("Content-Type"), Some("text/plain"));
+        assert_eq!(req.header("Message"), Some("nightly log"));
+    }
+
+    #[tokio::test]
+    async fn streams_files_with_their_size_as_content_length() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+        let path =
+            std::env::temp_dir().join(format!("ntfy-rs-streamed-{}.log", std::process::id()));
+        std::fs::write(&path, "x".repeat(20_000)).unwrap();
+
+        let msg = Message::builder("nightly log").build().unwrap();
+        let res = ntfy_file_streamed(&cli, msg, &path).await;
+        std::fs::remove_file(&path).unwrap();
+        res.unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.method, "PUT");
+        assert_eq!(req.header("Content-Length"), Some("20000"));
+        assert_eq!(req.header("Transfer-Encoding"), None);
+        assert_eq!(req.body.len(), 20_000);
+        assert_eq!(req.header("Filename"), path.file_name().unwrap().to_str());
+        assert_eq!(req.header("Message"), Some("nightly log"));
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_streamed_files() {
+        let cli = Client::new("https://ntfy.sh", "test", "uuid").unwrap();
+        let msg = Message::builder("hi").build().unwrap();
+        let res = ntfy_file_streamed(&cli, msg, "/nonexistent/ntfy-rs/file.txt").await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("could not read")));
+    }
+
+    #[tokio::test]
+    async fn escapes_line_breaks_in_upload_captions() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("nightly\nlog").build().unwrap();
+        ntfy_stream(&cli, msg, &b"ok"[..], "log.txt", None)
+            .await
+            .unwrap();
+        let msg = Message::builder("nightly\nlog")
+            .preserve_newlines(false)
+            .build()
+            .unwrap();
+        ntfy_stream(&cli, msg, &b"ok"[..], "log.txt", None)
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Message"), Some(r"nightly\nlog"));
+        assert_eq!(requests[1].header("Message"), Some("nightly log"));
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_files() {
+        let cli = Client::new("https://ntfy.sh", "test", "uuid").unwrap();
+        let msg = Message::builder("hi").build().unwrap();
+        let res = ntfy_file(&cli, msg, "/nonexistent/ntfy-rs/file.txt").await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("could not read")));
+    }
+
+    #[tokio::test]
+    async fn rejects_templated_file_uploads() {
+        let cli = Client::new("https://ntfy.sh", "test", "uuid").unwrap();
+        let msg = Message::builder("hi").template(true).build().unwrap();
+        let res = ntfy_file(&cli, msg, "/nonexistent/ntfy-rs/file.txt").await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("templating")));
+    }
+
+    #[tokio::test]
+    async fn sends_delays_as_header() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+        let at = chrono::Utc::now() + chrono::Duration::hours(1);
+
+        ntfy(&cli, Message::builder("hi").delay("30min").build().unwrap())
+            .await
+            .unwrap();
+        ntfy(
+            &cli,
+            Message::builder("hi").delay_until(at).build().unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Delay"), Some("1800s"));
+        assert_eq!(
+            requests[1].header("Delay"),
+            Some(at.timestamp().to_string().as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn forwards_to_email_with_authentication() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+
+        let msg = Message::builder("hi")
+            .email("phil@example.com")
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).await.unwrap();
+        let invalid = Message::builder("hi")
+            .email("not an address")
+            .build_unchecked();
+        assert!(matches!(
+            ntfy(&cli, invalid).await,
+            Err(NtfyError::InvalidInput(_))
+        ));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].header("Email"), Some("phil@example.com"));
+        assert_eq!(
+            requests[0].header("Authorization"),
+            Some("Bearer tk_secret")
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_call_header() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+
+        for number in ["+12223334444", "yes"] {
+            ntfy(&cli, Message::builder("hi").call(number).build().unwrap())
+                .await
+                .unwrap();
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Call"), Some("+12223334444"));
+        assert_eq!(requests[1].header("Call"), Some("yes"));
+    }
+
+    #[tokio::test]
+    async fn sends_icon_alongside_attachment() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi")
+            .icon("https://example.com/logo.png")
+            .attach("https://example.com/report.pdf");
+        ntfy(&cli, msg.build().unwrap()).await.unwrap();
+        let invalid = Message::builder("hi").icon("logo.png").build_unchecked();
+        assert!(matches!(ntfy(&cli, invalid).await, Err(NtfyError::Url(_))));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].header("Icon"),
+            Some("https://example.com/logo.png")
+        );
+        assert_eq!(
+            requests[0].header("Attach"),
+            Some("https://example.com/report.pdf")
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_markdown_header_only_if_enabled() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        for enabled in [true, false] {
+            let msg = Message::builder("**bold**")
+                .markdown(enabled)
+                .build()
+                .unwrap();
+            ntfy(&cli, msg).await.unwrap();
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Markdown"), Some("yes"));
+        assert_eq!(requests[1].header("Markdown"), None);
+    }
+
+    #[tokio::test]
+    async fn sends_cache_header_only_if_disabled() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        ntfy(&cli, Message::builder("hi").cache(true).build().unwrap())
+            .await
+            .unwrap();
+        ntfy(&cli, Message::builder("hi").cache(false).build().unwrap())
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Cache"), None);
+        assert_eq!(requests[1].header("Cache"), None);
+        assert_eq!(requests[2].header("Cache"), Some("no"));
+    }
+
+    #[tokio::test]
+    async fn sends_firebase_header_only_if_disabled() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        for enabled in [true, false] {
+            let msg = Message::builder("hi").firebase(enabled).build().unwrap();
+            ntfy(&cli, msg).await.unwrap();
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Firebase"), None);
+        assert_eq!(requests[1].header("Firebase"), Some("no"));
+    }
+
+    #[tokio::test]
+    async fn forwards_custom_headers() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi")
+            .header("X-Experimental", "on")
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).await.unwrap();
+
+        assert_eq!(server.requests()[0].header("X-Experimental"), Some("on"));
+    }
+
+    #[tokio::test]
+    async fn rejects_custom_headers_colliding_with_managed_ones() {
+        let cli = Client::new("http://127.0.0.1:9", "test", "uuid").unwrap();
+
+        for name in ["Title", "x-title", "t", "Prio"] {
+            let msg = Message::builder("hi")
+                .header(name, "oops")
+                .build_unchecked();
+            let res = ntfy(&cli, msg).await;
+            assert!(matches!(res, Err(NtfyError::InvalidInput(_))), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn joins_and_dedups_tag_slices() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi")
+            .tags_slice(["warning", "backup", "warning"])
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).await.unwrap();
+
+        assert_eq!(server.requests()[0].header("Tags"), Some("warning,backup"));
+    }
+
+    #[tokio::test]
+    async fn rejects_tags_containing_commas() {
+        let cli = Client::new("http://127.0.0.1:9", "test", "uuid").unwrap();
+
+        let msg = Message::builder("hi")
+            .tags_slice(["ok", "a,b"])
+            .build_unchecked();
+        let res = ntfy(&cli, msg).await;
+
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn omits_the_title_of_untitled_messages() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        ntfy(
+            &cli,
+            Message::builder("hi").title("Backup").build().unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Title"), None);
+        assert_eq!(requests[1].header("Title"), Some("Backup"));
+    }
+
+    #[test]
+    fn builds_suffixed_and_bare_topic_urls() {
+        let suffixed = Client::new("https://ntfy.sh", "backup", "1234").unwrap();
+        let bare = Client::new_topic("https://ntfy.sh/", "backup").unwrap();
+
+        assert_eq!(
+            topic_url(&suffixed, "").unwrap().as_str(),
+            "https://ntfy.sh/backup_1234"
+        );
+        assert_eq!(
+            topic_url(&suffixed, "/sse").unwrap().as_str(),
+            "https://ntfy.sh/backup_1234/sse"
+        );
+        assert_eq!(
+            topic_url(&bare, "").unwrap().as_str(),
+            "https://ntfy.sh/backup"
+        );
+        assert_eq!(
+            topic_url(&bare, "/json").unwrap().as_str(),
+            "https://ntfy.sh/backup/json"
+        );
+    }
+
+    #[tokio::test]
+    async fn publishes_to_bare_topics() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new_topic(&server.url, "backup").unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests()[0].path, "/backup");
+    }
+
+    #[tokio::test]
+    async fn publishes_json_to_the_server_root() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::with_token(&server.url, "backup", "1234", "tk_secret").unwrap();
+
+        let msg = Message::builder("hi")
+            .cache(false)
+            .header("X-Experimental", "on")
+            .build()
+            .unwrap();
+        ntfy_json(&cli, msg).await.unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/");
+        assert_eq!(req.header("Content-Type"), Some("application/json"));
+        assert_eq!(req.header("Authorization"), Some("Bearer tk_secret"));
+        assert_eq!(req.header("Cache"), Some("no"));
+        assert_eq!(req.header("X-Experimental"), Some("on"));
+        assert_eq!(req.body, br#"{"topic":"backup_1234","message":"hi"}"#);
+    }
+
+    #[tokio::test]
+    async fn rejects_managed_custom_headers_in_json() {
+        let cli = Client::new("http://127.0.0.1:9", "backup", "1234").unwrap();
+
+        let msg = Message::builder("hi")
+            .header("Title", "oops")
+            .build_unchecked();
+        let res = ntfy_json(&cli, msg).await;
+
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn sends_many_notifications_in_order() {
+        let server = TestServer::start(vec![
+            response(200, &[], PUBLISHED),
+            response(200, &[], PUBLISHED),
+            response(403, &[], r#"{"code":40301,"http":403,"error":"forbidden"}"#),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msgs = ["one", "two", "three"]
+            .map(|text| Message::builder(text).build().unwrap())
+            .to_vec();
+        let results = ntfy_many(&cli, msgs, 1).await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(matches!(
+            results[2],
+            Err(NtfyError::Server { status: 403, .. })
+        ));
+        let bodies: Vec<_> = server.requests().into_iter().map(|req| req.body).collect();
+        assert_eq!(
+            bodies,
+            [b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_the_input_order_when_sending_concurrently() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msgs = vec![
+            Message::builder("one").build().unwrap(),
+            Message::builder("bad").click("not a url").build_unchecked(),
+            Message::builder("three").build().unwrap(),
+        ];
+        let results = ntfy_many(&cli, msgs, 3).await;
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(NtfyError::Url(_))));
+        assert!(results[2].is_ok());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn streams_attachments_in_chunks() {
+        use tokio::io::AsyncWriteExt;
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        for content_length in [None, Some(16)] {
+            let (mut writer, reader) = tokio::io::duplex(4);
+            tokio::spawn(async move {
+                for chunk in ["live ", "log ", "capture"] {
+                    writer.write_all(chunk.as_bytes()).await.unwrap();
+                    tokio::task::yield_now().await;
+                }
+            });
+
+            let msg = Message::builder("tail").build().unwrap();
+            ntfy_stream(&cli, msg, reader, "app.log", content_length)
+                .await
+                .unwrap();
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Transfer-Encoding"), Some("chunked"));
+        assert_eq!(requests[1].header("Content-Length"), Some("16"));
+        for req in &requests {
+            assert_eq!(req.method, "PUT");
+            assert_eq!(req.body, b"live log capture");
+            assert_eq!(req.header("Filename"), Some("app.log"));
+            assert_eq!(req.header("Message"), Some("tail"));
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_streams_combined_with_attachment_urls() {
+        let cli = Client::new("https://ntfy.sh", "test", "uuid").unwrap();
+        let msg = Message::builder("hi")
+            .attach("https://example.com/a.txt")
+            .build()
+            .unwrap();
+        let res = ntfy_stream(&cli, msg, tokio::io::empty(), "a.txt", None).await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn overrides_the_content_type() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::builder(r#"{"disk":95}"#)
+            .content_type("application/json")
+            .markdown(true)
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).await.unwrap();
+        let msg = Message::builder("a,b")
+            .content_type("text/csv")
+            .build()
+            .unwrap();
+        ntfy_stream(&cli, msg, tokio::io::empty(), "report.txt", None)
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Content-Type"), Some("application/json"));
+        assert_eq!(requests[0].header("Markdown"), Some("yes"));
+        assert_eq!(requests[0].body, br#"{"disk":95}"#);
+        assert_eq!(requests[1].header("Content-Type"), Some("text/csv"));
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_messages_before_sending() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+        let large = "x".repeat(4097);
+
+        let res = ntfy(&cli, Message::builder(&large).build().unwrap()).await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("4097 bytes")));
+        let res = ntfy_json(&cli, Message::builder(&large).build().unwrap()).await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+        assert!(server.requests().is_empty());
+
+        ntfy(&cli, Message::builder(&"x".repeat(4096)).build().unwrap())
+            .await
+            .unwrap();
+        let raised = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .max_message_bytes(8192)
+            .build()
+            .unwrap();
+        ntfy(&raised, Message::builder(&large).build().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn publishes_triggers_without_body() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = Message::trigger()
+            .title("Backup started")
+            .tags("floppy_disk")
+            .priority(Priority::High)
+            .build()
+            .unwrap();
+        assert_eq!(
+            msg.to_string(),
+            "[Backup started] triggered (priority: high, tags: floppy_disk)"
+        );
+        ntfy(&cli, msg).await.unwrap();
+
+        let req = &server.requests()[0];
+        assert!(req.body.is_empty());
+        assert_eq!(req.header("Transfer-Encoding"), None);
+        assert_eq!(req.header("Title"), Some("Backup started"));
+        assert_eq!(req.header("Tags"), Some("floppy_disk"));
+        assert_eq!(req.header("Priority"), Some("4"));
+    }
+
+    #[tokio::test]
+    async fn sends_messages_created_from_text() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        ntfy(&cli, "hello".into()).await.unwrap();
+        ntfy(&cli, String::from("world").into()).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].body, b"hello");
+        assert_eq!(requests[1].body, b"world");
+        assert_eq!(requests[1].header("Title"), None);
+        assert_eq!(
+            Message::from("hello").to_string(),
+            Message::builder("hello").build().unwrap().to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn overrides_the_server_per_message() {
+        let primary = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let fallback = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::with_token(&primary.url, "test", "uuid", "tk_secret").unwrap();
+
+        ntfy(&cli, Message::builder("one").build().unwrap())
+            .await
+            .unwrap();
+        let msg = Message::builder("two")
+            .server(&format!("{}/", fallback.url))
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).await.unwrap();
+        let msg = Message::builder("three")
+            .server(&fallback.url)
+            .build()
+            .unwrap();
+        ntfy_json(&cli, msg).await.unwrap();
+
+        assert_eq!(primary.requests().len(), 1);
+        assert_eq!(primary.requests()[0].body, b"one");
+        let requests = fallback.requests();
+        assert_eq!(requests[0].path, "/test_uuid");
+        assert_eq!(requests[0].body, b"two");
+        assert_eq!(
+            requests[0].header("Authorization"),
+            Some("Bearer tk_secret")
+        );
+        assert_eq!(requests[1].path, "/");
+    }
+
+    #[tokio::test]
+    async fn compresses_large_bodies_if_enabled() {
+        use std::io::Read;
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .gzip_threshold(16)
+            .build()
+            .unwrap();
+        let large = "# Report\n".repeat(100);
+
+        ntfy(&cli, Message::builder(&large).build().unwrap())
+            .await
+            .unwrap();
+        ntfy(&cli, Message::builder("short").build().unwrap())
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Content-Encoding"), Some("gzip"));
+        assert!(requests[0].body.len() < large.len());
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(requests[0].body.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, large);
+        assert_eq!(requests[1].header("Content-Encoding"), None);
+        assert_eq!(requests[1].body, b"short");
+    }
+
+    #[tokio::test]
+    async fn sends_x_prefixed_headers_if_enabled() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .uuid("uuid")
+            .token("tk_secret")
+            .use_x_prefix(true)
+            .build()
+            .unwrap();
+
+        let msg = Message::builder("hi")
+            .title("Disk")
+            .priority(Priority::High)
+            .content_type("text/csv")
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).await.unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.header("X-Title"), Some("Disk"));
+        assert_eq!(req.header("X-Priority"), Some("4"));
+        assert_eq!(req.header("Title"), None);
+        assert_eq!(req.header("Authorization"), Some("Bearer tk_secret"));
+        assert_eq!(req.header("Content-Type"), Some("text/csv"));
+    }
+
+    #[tokio::test]
+    async fn applies_the_default_priority() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .default_priority(Priority::High)
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        let msg = Message::builder("hi")
+            .priority(Priority::Min)
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).await.unwrap();
+        ntfy_json(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Priority"), Some("4"));
+        assert_eq!(requests[1].header("Priority"), Some("1"));
+        let json: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+        assert_eq!(json["priority"], 4);
+    }
+
+    #[tokio::test]
+    async fn sends_default_headers_unless_overridden() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .default_header("X-Api-Key", "gateway")
+            .default_header("X-Trace", "default")
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        let msg = Message::builder("hi")
+            .header("x-trace", "42")
+            .build()
+            .unwrap();
+        ntfy(&cli, msg).await.unwrap();
+        ntfy_json(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("X-Api-Key"), Some("gateway"));
+        assert_eq!(requests[0].header("X-Trace"), Some("default"));
+        assert_eq!(requests[1].header("X-Api-Key"), Some("gateway"));
+        assert_eq!(requests[1].header("X-Trace"), Some("42"));
+        assert_eq!(
+            requests[1]
+                .headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("X-Trace"))
+                .count(),
+            1
+        );
+        assert_eq!(requests[2].header("X-Api-Key"), Some("gateway"));
+        assert_eq!(requests[2].header("X-Trace"), Some("default"));
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_action_headers_locally() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .max_header_bytes(256)
+            .build()
+            .unwrap();
+        let action = |n: usize| Action::View {
+            label: format!("Open {n}"),
+            url: format!("https://backup.example.com/{}", "x".repeat(100)),
+            clear: false,
+            extra: Default::default(),
+        };
+        let msg = Message::builder("hi")
+            .actions((0..3).map(action).collect())
+            .click("https://backup.example.com")
+            .build()
+            .unwrap();
+
+        let res = ntfy(&cli, msg.clone()).await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("ntfy_json")));
+        assert!(server.requests().is_empty());
+
+        ntfy_json(&cli, msg).await.unwrap();
+        let small = Message::builder("hi")
+            .actions(vec![action(0)])
+            .build()
+            .unwrap();
+        ntfy(&cli, small).await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn publishes_unifiedpush_messages_as_opaque_bodies() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("up")
+            .token("tk_secret")
+            .default_priority(Priority::High)
+            .build()
+            .unwrap();
+
+        ntfy_up(&cli, &b"\x00encrypted"[..]).await.unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/up?up=1");
+        assert_eq!(req.body, b"\x00encrypted");
+        assert_eq!(req.header("Authorization"), Some("Bearer tk_secret"));
+        for name in ["Title", "Tags", "Priority", "Firebase"] {
+            assert_eq!(req.header(name), None);
+        }
+
+        let res = ntfy_up(&cli, vec![0; 4097]).await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(_))));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn compresses_streamed_uploads_above_the_gzip_threshold() {
+        use std::io::Read;
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .gzip_threshold(1024)
+            .build()
+            .unwrap();
+        let log = "backup step finished\n".repeat(500);
+
+        for (reader, len) in [(log.clone(), Some(log.len() as u64)), (log.clone(), None)] {
+            let msg = Message::builder("log").build().unwrap();
+            ntfy_stream(&cli, msg, std::io::Cursor::new(reader), "backup.log", len)
+                .await
+                .unwrap();
+        }
+        let msg = Message::builder("small").build().unwrap();
+        ntfy_stream(&cli, msg, &b"ok"[..], "ok.txt", Some(2))
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        for req in &requests[..2] {
+            assert_eq!(req.header("Content-Encoding"), Some("gzip"));
+            assert_eq!(req.header("Content-Length"), None);
+            assert_eq!(req.header("Filename"), Some("backup.log"));
+            assert_eq!(req.header("Content-Type"), Some("text/plain"));
+            assert!(req.body.len() < log.len());
+
+            let mut body = String::new();
+            flate2::read::GzDecoder::new(req.body.as_slice())
+                .read_to_string(&mut body)
+                .unwrap();
+            assert_eq!(body, log);
+        }
+        assert_eq!(requests[2].header("Content-Encoding"), None);
+        assert_eq!(requests[2].header("Content-Length"), Some("2"));
+        assert_eq!(requests[2].body, b"ok");
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_upload_captions_before_sending() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .max_message_bytes(16)
+            .build()
+            .unwrap();
+        let caption = "a caption longer than sixteen bytes";
+
+        let msg = Message::builder(caption).build().unwrap();
+        let res = ntfy_file(&cli, msg, "/nonexistent/ntfy-rs/file.txt").await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("16 bytes")));
+
+        let msg = Message::builder(caption).build().unwrap();
+        let res = ntfy_stream(&cli, msg, &b"ok"[..], "ok.txt", None).await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("16 bytes")));
+        assert!(server.requests().is_empty());
+    }
+
+    /// Accepts connections but never responds, to simulate a stalled endpoint.
+    async fn stalled_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn times_out_slow_notifications_without_blocking_the_others() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let stalled = stalled_server().await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msgs = vec![
+            Message::builder("one").build().unwrap(),
+            Message::builder("slow").server(&stalled).build().unwrap(),
+            Message::builder("three").build().unwrap(),
+        ];
+        let timeout = Duration::from_millis(200);
+        let results = ntfy_many_timeout(&cli, msgs, 3, Some(timeout), None).await;
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(NtfyError::Elapsed { timeout: t }) if t == timeout));
+        assert!(results[2].is_ok());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn fails_unfinished_notifications_at_the_deadline() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let stalled = stalled_server().await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msgs = vec![
+            Message::builder("slow").server(&stalled).build().unwrap(),
+            Message::builder("fast").build().unwrap(),
+        ];
+        let deadline = Duration::from_millis(200);
+        let results =
+            ntfy_many_timeout(&cli, msgs, 2, Some(Duration::from_secs(10)), Some(deadline)).await;
+
+        assert!(matches!(results[0], Err(NtfyError::Deadline { deadline: d }) if d == deadline));
+        assert!(results[1].is_ok());
+    }
+
+    #[tokio::test]
+    async fn applies_the_default_tags() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .default_tags(&["backup", "server"])
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+        let msg = Message::builder("hi").tags("warning").build().unwrap();
+        ntfy(&cli, msg).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("Tags"), Some("backup,server"));
+        assert_eq!(requests[1].header("Tags"), Some("warning"));
+    }
+
+    #[tokio::test]
+    async fn lets_messages_opt_out_of_the_defaults() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .default_tags(&["backup"])
+            .default_priority(Priority::High)
+            .build()
+            .unwrap();
+
+        let msgs = [
+            Message::builder("hi").no_default_tags().build().unwrap(),
+            Message::builder("hi").build().unwrap(),
+            Message::builder("hi")
+                .no_default_priority()
+                .build()
+                .unwrap(),
+            Message::builder("hi").clear_defaults().build().unwrap(),
+        ];
+        for msg in msgs {
+            ntfy_json(&cli, msg.clone()).await.unwrap();
+            ntfy(&cli, msg).await.unwrap();
+        }
+
+        let requests = server.requests();
+        let sent: Vec<_> = requests
+            .chunks(2)
+            .map(|reqs| {
+                let json: serde_json::Value = serde_json::from_slice(&reqs[0].body).unwrap();
+                (reqs[1].header("Tags"), reqs[1].header("Priority"), json)
+            })
+            .collect();
+        assert_eq!((sent[0].0, sent[0].1), (None, Some("4")));
+        assert_eq!((sent[1].0, sent[1].1), (Some("backup"), Some("4")));
+        assert_eq!((sent[2].0, sent[2].1), (Some("backup"), None));
+        assert_eq!((sent[3].0, sent[3].1), (None, None));
+        assert_eq!(sent[0].2.get("tags"), None);
+        assert_eq!(sent[1].2["tags"], serde_json::json!(["backup"]));
+        assert_eq!(sent[3].2.get("priority"), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_messages_to_insecure_servers() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let msg = || {
+            Message::builder("hi")
+                .server("http://ntfy.sh")
+                .build()
+                .unwrap()
+        };
+        assert!(matches!(
+            ntfy(&cli, msg()).await,
+            Err(NtfyError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            ntfy_json(&cli, msg()).await,
+            Err(NtfyError::InvalidInput(_))
+        ));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn publishes_with_the_configured_method() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .publish_method(reqwest::Method::PUT)
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").title("Put").build().unwrap())
+            .await
+            .unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.method, "PUT");
+        assert_eq!(req.path, "/test");
+        assert_eq!(req.header("Title"), Some("Put"));
+        assert_eq!(req.body, b"hi");
+    }
+
+    #[tokio::test]
+    async fn resends_redirected_publishes_with_method_body_and_auth() {
+        let server = TestServer::start(vec![
+            response(301, &[("Location", "/moved/test_uuid")], ""),
+            response(200, &[], PUBLISHED),
+        ])
+        .await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .uuid("uuid")
+            .token("tk_secret")
+            .redirect(Redirect::SameOrigin(3))
+            .build()
+            .unwrap();
+
+        ntfy(&cli, Message::builder("hi").title("Moved").build().unwrap())
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        let req = &requests[1];
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/moved/test_uuid");
+        assert_eq!(req.body, b"hi");
+        assert_eq!(req.header("Title"), Some("Moved"));
+        assert_eq!(req.header("Authorization"), Some("Bearer tk_secret"));
+    }
+
+    #[tokio::test]
+    async fn drops_credentials_on_redirects_to_other_origins() {
+        let target = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let location = format!("{}/test_uuid", target.url);
+        let server = TestServer::start(vec![response(307, &[("Location", &location)], "")]).await;
+        let cli = |redirect| {
+            Client::builder()
+                .server(&server.url)
+                .topic("test")
+                .uuid("uuid")
+                .token("tk_secret")
+                .redirect(redirect)
+                .build()
+                .unwrap()
+        };
+
+        ntfy(
+            &cli(Redirect::default()),
+            Message::builder("hi").build().unwrap(),
+        )
+        .await
+        .unwrap();
+        let req = &target.requests()[0];
+        assert_eq!(
+            (req.method.as_str(), req.body.as_slice()),
+            ("POST", &b"hi"[..])
+        );
+        assert_eq!(req.header("Authorization"), None);
+
+        let res = ntfy(
+            &cli(Redirect::SameOrigin(3)),
+            Message::builder("hi").build().unwrap(),
+        )
+        .await;
+        assert!(matches!(res, Err(NtfyError::Server { status: 307, .. })));
+        assert_eq!(target.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn does_not_follow_see_other_for_publishes() {
+        let server = TestServer::start(vec![
+            response(303, &[("Location", "/elsewhere")], ""),
+            response(200, &[], PUBLISHED),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap()).await;
+
+        assert!(matches!(res, Err(NtfyError::Server { status: 303, .. })));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn publishes_batches_with_independent_outcomes() {
+        let server = TestServer::start(vec![
+            response(200, &[], PUBLISHED),
+            response(403, &[], r#"{"code":40301,"http":403,"error":"forbidden"}"#),
+            response(200, &[], PUBLISHED),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let items = vec![
+            (
+                Topic::new("alerts"),
+                Message::builder("one").build().unwrap(),
+            ),
+            (
+                Topic::new("locked"),
+                Message::builder("two").build().unwrap(),
+            ),
+            (
+                Topic::new("backups"),
+                Message::builder("three").build().unwrap(),
+            ),
+            (
+                Topic::new("in valid"),
+                Message::builder("four").build().unwrap(),
+            ),
+        ];
+        let results = publish_batch(&cli, items).await;
+
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1],
+            Err(NtfyError::Server { status: 403, .. })
+        ));
+        assert!(results[2].is_ok());
+        assert!(matches!(results[3], Err(NtfyError::InvalidInput(_))));
+
+        let requests = server.requests();
+        let topics: Vec<_> = requests
+            .iter()
+            .map(|req| {
+                serde_json::from_slice::<serde_json::Value>(&req.body).unwrap()["topic"].clone()
+            })
+            .collect();
+        assert_eq!(topics, ["alerts", "locked", "backups"]);
+        assert_eq!(server.connections(), 1);
+    }
+
+    #[tokio::test]
+    async fn publishes_events_via_to_message() {
+        struct Event {
+            level: Priority,
+            source: &'static str,
+            summary: &'static str,
+            detail: &'static str,
+        }
+
+        impl ToMessage for Event {
+            fn to_message(&self) -> Message {
+                Message::builder(self.detail)
+                    .title(&format!("{}: {}", self.source, self.summary))
+                    .priority(self.level)
+                    .tags(self.source)
+                    .build_unchecked()
+            }
+        }
+
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+        let event = Event {
+            level: Priority::High,
+            source: "backup",
+            summary: "failed",
+            detail: "disk full",
+        };
+
+        ntfy_event(&cli, &event).await.unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.header("Title"), Some("backup: failed"));
+        assert_eq!(req.header("Priority"), Some("4"));
+        assert_eq!(req.header("Tags"), Some("backup"));
+        assert_eq!(req.body, b"disk full");
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// A token bucket pacing the notifications of a `Client` and all of its clones.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// The maximum number of notifications sent at once.
+    burst: u32,
+
+    /// The time it takes to replenish one notification.
+    interval: Duration,
+
+    /// The tokens left and when they were last replenished.
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    /// Constructs a full bucket of `burst` notifications, replenishing one every `interval`.
+    pub(crate) fn new(burst: u32, interval: Duration) -> Self {
+        let burst = burst.max(1);
+        Self {
+            burst,
+            interval,
+            bucket: Mutex::new((f64::from(burst), Instant::now())),
+        }
+    }
+
+    /// Waits until a notification may be sent and takes its token.
+    ///
+    /// Waiters queue fairly, as the lock is held while waiting for the next token.
+    pub(crate) async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+        let (tokens, updated) = *bucket;
+        let now = Instant::now();
+        let tokens = if self.interval.is_zero() {
+            f64::from(self.burst)
+        } else {
+            let replenished =
+                now.duration_since(updated).as_secs_f64() / self.interval.as_secs_f64();
+            (tokens + replenished).min(f64::from(self.burst))
+        };
+
+        if tokens >= 1.0 {
+            *bucket = (tokens - 1.0, now);
+            return;
+        }
+
+        tokio::time::sleep(self.interval.mul_f64(1.0 - tokens)).await;
+        *bucket = (0.0, Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_server::{response, TestServer, PUBLISHED};
+    use crate::{ntfy, Client, Message};
+
+    #[tokio::test]
+    async fn paces_bursts_beyond_the_bucket_size() {
+        let limiter = RateLimiter::new(2, Duration::from_millis(50));
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn shares_the_limit_between_clones() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("test")
+            .rate_limit(1, Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let clone = cli.clone();
+        let start = Instant::now();
+
+        ntfy(&cli, Message::builder("one").build().unwrap())
+            .await
+            .unwrap();
+        ntfy(&clone, Message::builder("two").build().unwrap())
+            .await
+            .unwrap();
+        ntfy(&cli, Message::builder("three").build().unwrap())
+            .await
+            .unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(server.requests().len(), 3);
+    }
+}
@@ -0,0 +1,479 @@
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+
+use crate::{authorize, send, Action, Client, MessageId, NtfyError, Priority};
+
+/// A message received from a subscription to a topic, see [JSON message format](https://docs.ntfy.sh/subscribe/api/#json-message-format).
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct ReceivedMessage {
+    /// The randomly chosen message identifier.
+    pub id: String,
+
+    /// The Unix timestamp of the message.
+    pub time: i64,
+
+    /// The type of the event, i.e. `open`, `keepalive`, `message` or `poll_request`.
+    pub event: String,
+
+    /// The topic(s) the message is associated with, separated by commas.
+    pub topic: String,
+
+    /// The body text of the message, only set for `message` events. Base64 encoded if
+    /// `encoding` is `base64`, see `raw_body`.
+    pub message: Option<String>,
+
+    /// The encoding of `message`, `base64` for binary bodies ntfy couldn't send as text, e.g. of
+    /// UnifiedPush messages. Plain UTF-8 text if `None`.
+    pub encoding: Option<String>,
+
+    /// The title of the message.
+    pub title: Option<String>,
+
+    /// The tags of the message, e.g. emoji short codes.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// The priority of the message, `Priority::Default` if not sent. The integer value ntfy uses
+    /// is available via `Priority::as_u8`.
+    #[serde(default, deserialize_with = "priority")]
+    pub priority: Priority,
+
+    /// The URL which is opened when the notification is tapped.
+    pub click: Option<String>,
+
+    /// The file attached to the message.
+    pub attachment: Option<Attachment>,
+
+    /// The action buttons of the message.
+    #[serde(default)]
+    pub actions: Vec<Action>,
+
+    /// All fields not modeled by this crate, e.g. ones added by newer ntfy versions, as they
+    /// were received.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ReceivedMessage {
+    /// Returns the `id` of the message, e.g. for `Since::Id`.
+    pub fn message_id(&self) -> MessageId {
+        MessageId::from(&self.id)
+    }
+
+    /// Returns the raw JSON value of the field `name` if it isn't modeled by this crate, see
+    /// `extra`.
+    pub fn raw(&self, name: &str) -> Option<&serde_json::Value> {
+        self.extra.get(name)
+    }
+
+    /// Returns the bytes of the body, decoding it if it's base64 encoded. Empty if the message
+    /// has no body.
+    ///
+    /// Fails with `NtfyError::InvalidInput` if the body isn't valid base64 or the encoding is
+    /// unknown.
+    pub fn raw_body(&self) -> Result<Vec<u8>, NtfyError> {
+        let message = self.message.as_deref().unwrap_or_default();
+        match self.encoding.as_deref() {
+            None | Some("") => Ok(message.as_bytes().to_vec()),
+            Some("base64") => STANDARD.decode(message).map_err(|e| {
+                NtfyError::InvalidInput(format!("message body is not valid base64: {e}"))
+            }),
+            Some(encoding) => Err(NtfyError::InvalidInput(format!(
+                "unknown message encoding `{encoding}`"
+            ))),
+        }
+    }
+
+    /// Returns `time` as a point in time, `None` if out of range.
+    pub fn received_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(self.time, 0)
+    }
+
+    /// Returns how long ago the message was published at `now`, e.g. `chrono::Utc::now()`.
+    ///
+    /// Messages from the future due to clock skew have an age of zero.
+    pub fn age(&self, now: chrono::DateTime<chrono::Utc>) -> Duration {
+        now.timestamp()
+            .saturating_sub(self.time)
+            .try_into()
+            .map_or(Duration::ZERO, Duration::from_secs)
+    }
+}
+
+/// A file attached to a received message.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct Attachment {
+    /// The name of the attachment, e.g. `flower.jpg`.
+    pub name: String,
+
+    /// The URL the attachment can be downloaded from.
+    pub url: String,
+
+    /// The mime type of the attachment, only set if uploaded to the ntfy server.
+    #[serde(rename = "type")]
+    pub mime_type: Option<String>,
+
+    /// The size of the attachment in bytes, only set if uploaded to the ntfy server.
+    pub size: Option<u64>,
+
+    /// The Unix timestamp at which the attachment expires, only set if uploaded to the ntfy server.
+    pub expires: Option<i64>,
+}
+
+impl Attachment {
+    /// Returns `expires` as a point in time, `None` if not set or out of range.
+    pub fn expires_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(self.expires?, 0)
+    }
+
+    /// Returns whether the attachment has expired at `now`, e.g. `chrono::Utc::now()`, and
+    /// can't be downloaded anymore. Attachments without an expiry never expire.
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.expires_at().is_some_and(|expires| expires <= now)
+    }
+
+    /// Downloads the attachment with the HTTP client of `cli`.
+    ///
+    /// The credentials of `cli` are only sent if the attachment is hosted on its server. Fails
+    /// with `NtfyError::InvalidInput` without sending anything if the attachment has expired.
+    pub async fn download(&self, cli: &Client) -> Result<Bytes, NtfyError> {
+        if self.is_expired(chrono::Utc::now()) {
+            return Err(NtfyError::InvalidInput(format!(
+                "attachment `{}` has expired",
+                self.name
+            )));
+        }
+
+        let url = reqwest::Url::parse(&self.url)?;
+        let server = reqwest::Url::parse(&cli.server)?;
+        let mut req = cli.http_client.get(url.clone());
+        if url.origin() == server.origin() {
+            crate::validate::secure(&url, cli.allow_insecure)?;
+            req = authorize(cli, req)?;
+        }
+
+        Ok(send(cli, req).await?.bytes().await?)
+    }
+}
+
+/// Deserializes the integer priority ntfy sends, treating `0` like an absent priority.
+fn priority<'de, D>(deserializer: D) -> Result<Priority, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match <Option<u8> as serde::Deserialize>::deserialize(deserializer)? {
+        None | Some(0) => Ok(Priority::Default),
+        Some(value) => Priority::try_from(value).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Decodes a single JSON message, returning `None` for `open` and `keepalive` events.
+pub(crate) fn decode(json: &str) -> Option<Result<ReceivedMessage, crate::NtfyError>> {
+    match serde_json::from_str::<ReceivedMessage>(json) {
+        Ok(msg) if is_control(&msg.event) => None,
+        parsed => Some(parsed.map_err(crate::NtfyError::from)),
+    }
+}
+
+/// Returns whether `event` only signals the state of the connection.
+pub(crate) fn is_control(event: &str) -> bool {
+    matches!(event, "open" | "keepalive")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_json_lines() {
+        let lines = [
+            r#"{"id":"a","time":1,"event":"open","topic":"t"}"#,
+            r#"{"id":"b","time":2,"event":"message","topic":"t","message":"hi"}"#,
+            r#"{"id":"c","time":3,"event":"keepalive","topic":"t"}"#,
+            r#"{"id":"d","time":4,"event":"message","topic":"t","message":"bye","title":"Bye"}"#,
+        ];
+        let messages: Vec<_> = lines
+            .into_iter()
+            .filter_map(decode)
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].id, "b");
+        assert_eq!(messages[0].message.as_deref(), Some("hi"));
+        assert_eq!(messages[1].title.as_deref(), Some("Bye"));
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        assert!(matches!(
+            decode(r#"{"id":"a"}"#),
+            Some(Err(crate::NtfyError::Decode(_)))
+        ));
+    }
+
+    #[test]
+    fn deserializes_attachments_and_actions() {
+        let json = r#"{
+            "id": "sPs71M8A2T",
+            "time": 1673542291,
+            "expires": 1673585491,
+            "event": "message",
+            "topic": "mytopic",
+            "title": "Backup",
+            "message": "Backup of server.tar.gz finished",
+            "priority": 4,
+            "tags": ["floppy_disk", "white_check_mark"],
+            "click": "https://backup.example.com/logs",
+            "attachment": {
+                "name": "server.log",
+                "type": "text/plain; charset=utf-8",
+                "size": 1234,
+                "expires": 1673553091,
+                "url": "https://ntfy.sh/file/sPs71M8A2T.txt"
+            },
+            "actions": [
+                {"id": "a1", "action": "view", "label": "Open", "url": "https://backup.example.com", "clear": true},
+                {"id": "a2", "action": "http", "label": "Retry", "url": "https://backup.example.com/retry", "method": "PUT", "headers": {"X-Token": "abc"}, "body": "now"},
+                {"id": "a3", "action": "broadcast", "label": "Take photo", "extras": {"cmd": "pic"}}
+            ]
+        }"#;
+        let msg: ReceivedMessage = serde_json::from_str(json).unwrap();
+
+        assert_eq!(msg.tags, ["floppy_disk", "white_check_mark"]);
+        assert_eq!(msg.priority, Priority::High);
+        assert_eq!(
+            msg.click.as_deref(),
+            Some("https://backup.example.com/logs")
+        );
+        assert_eq!(
+            msg.attachment,
+            Some(Attachment {
+                name: "server.log".into(),
+                url: "https://ntfy.sh/file/sPs71M8A2T.txt".into(),
+                mime_type: Some("text/plain; charset=utf-8".into()),
+                size: Some(1234),
+                expires: Some(1673553091),
+            })
+        );
+        assert_eq!(
+            msg.actions,
+            [
+                Action::View {
+                    label: "Open".into(),
+                    url: "https://backup.example.com".into(),
+                    clear: true,
+                    extra: Default::default(),
+                },
+                Action::Http {
+                    label: "Retry".into(),
+                    url: "https://backup.example.com/retry".into(),
+                    method: Some("PUT".into()),
+                    headers: [("X-Token".to_string(), "abc".to_string())].into(),
+                    body: Some("now".into()),
+                    clear: false,
+                    extra: Default::default(),
+                },
+                Action::Broadcast {
+                    label: "Take photo".into(),
+                    intent: None,
+                    extras: [("cmd".to_string(), "pic".to_string())].into(),
+                    clear: false,
+                    extra: Default::default(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn converts_attachment_expiry_to_a_point_in_time() {
+        let mut attachment = Attachment {
+            name: "server.log".into(),
+            url: "https://ntfy.sh/file/sPs71M8A2T.txt".into(),
+            mime_type: None,
+            size: None,
+            expires: Some(1673553091),
+        };
+        let expires = chrono::DateTime::from_timestamp(1673553091, 0).unwrap();
+
+        assert_eq!(attachment.expires_at(), Some(expires));
+        assert_eq!(attachment.expires, Some(1673553091));
+        assert!(!attachment.is_expired(expires - chrono::Duration::seconds(1)));
+        assert!(attachment.is_expired(expires));
+        assert!(attachment.is_expired(expires + chrono::Duration::days(1)));
+
+        attachment.expires = None;
+        assert_eq!(attachment.expires_at(), None);
+        assert!(!attachment.is_expired(expires));
+
+        attachment.expires = Some(i64::MAX);
+        assert_eq!(attachment.expires_at(), None);
+    }
+
+    #[tokio::test]
+    async fn downloads_attachments_with_the_client() {
+        use crate::test_server::{response, TestServer};
+
+        let server = TestServer::start(vec![response(200, &[], "log line")]).await;
+        let cli = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+        let mut attachment = Attachment {
+            name: "server.log".into(),
+            url: format!("{}/file/sPs71M8A2T.txt", server.url),
+            mime_type: None,
+            size: Some(8),
+            expires: Some(chrono::Utc::now().timestamp() + 3600),
+        };
+
+        assert_eq!(&attachment.download(&cli).await.unwrap()[..], b"log line");
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/file/sPs71M8A2T.txt");
+        assert_eq!(
+            requests[0].header("Authorization"),
+            Some("Bearer tk_secret")
+        );
+
+        attachment.expires = Some(chrono::Utc::now().timestamp() - 1);
+        let res = attachment.download(&cli).await;
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("expired")));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_credentials_from_other_hosts() {
+        use crate::test_server::{response, TestServer};
+
+        let files = TestServer::start(vec![response(200, &[], "log line")]).await;
+        let cli = Client::with_token("https://ntfy.sh", "test", "uuid", "tk_secret").unwrap();
+        let attachment = Attachment {
+            name: "server.log".into(),
+            url: format!("{}/server.log", files.url),
+            mime_type: None,
+            size: None,
+            expires: None,
+        };
+
+        attachment.download(&cli).await.unwrap();
+        assert_eq!(files.requests()[0].header("Authorization"), None);
+    }
+
+    #[test]
+    fn deserializes_priorities() {
+        let priority = |json: &str| {
+            serde_json::from_str::<ReceivedMessage>(&format!(
+                r#"{{"id":"x","time":1,"event":"message","topic":"t"{json}}}"#
+            ))
+            .map(|msg| msg.priority)
+        };
+
+        assert_eq!(priority("").unwrap(), Priority::Default);
+        assert_eq!(priority(r#","priority":0"#).unwrap(), Priority::Default);
+        assert_eq!(priority(r#","priority":1"#).unwrap(), Priority::Min);
+        assert_eq!(priority(r#","priority":5"#).unwrap(), Priority::Max);
+        assert!(priority(r#","priority":6"#).is_err());
+    }
+
+    #[test]
+    fn converts_the_publish_time() {
+        let msg: ReceivedMessage =
+            serde_json::from_str(r#"{"id":"x","time":1673542291,"event":"message","topic":"t"}"#)
+                .unwrap();
+        let published = chrono::DateTime::from_timestamp(1673542291, 0).unwrap();
+
+        assert_eq!(msg.received_at(), Some(published));
+        assert_eq!(
+            msg.age(published + chrono::Duration::seconds(90)),
+            Duration::from_secs(90)
+        );
+        assert_eq!(msg.age(published), Duration::ZERO);
+        assert_eq!(
+            msg.age(published - chrono::Duration::minutes(5)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_fields() {
+        let msg = decode(
+            r#"{"id":"a","time":1,"event":"message","topic":"t","priority":4,"expires":1700000000,"content_type":"text/markdown"}"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(msg.priority, Priority::High);
+        assert_eq!(msg.raw("expires"), Some(&serde_json::json!(1700000000)));
+        assert_eq!(
+            msg.raw("content_type"),
+            Some(&serde_json::json!("text/markdown"))
+        );
+        assert_eq!(msg.extra.len(), 2);
+        assert_eq!(msg.raw("topic"), None);
+    }
+
+    #[test]
+    fn decodes_binary_bodies() {
+        let binary = decode(
+            r#"{"id":"a","time":1,"event":"message","topic":"t","message":"/wD+","encoding":"base64"}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(binary.raw_body().unwrap(), [0xff, 0x00, 0xfe]);
+
+        let text = decode(r#"{"id":"b","time":2,"event":"message","topic":"t","message":"hi"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(text.raw_body().unwrap(), b"hi");
+
+        let unknown = ReceivedMessage {
+            encoding: Some("rot13".into()),
+            ..text
+        };
+        assert!(matches!(
+            unknown.raw_body(),
+            Err(NtfyError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn converts_into_messages_for_republishing() {
+        let received = decode(
+            r#"{
+                "id": "a", "time": 1, "event": "message", "topic": "alerts",
+                "message": "Disk full", "title": "backup-01", "tags": ["warning", "disk"],
+                "priority": 5, "click": "https://backup.example.com",
+                "actions": [{"id": "a1", "action": "view", "label": "Open", "url": "https://backup.example.com"}]
+            }"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        let msg = crate::Message::from_received(&received).unwrap();
+        assert_eq!(msg.message, "Disk full");
+        assert_eq!(msg.title.as_deref(), Some("backup-01"));
+        assert_eq!(msg.tags, ["warning", "disk"]);
+        assert_eq!(msg.priority, Some(Priority::Max));
+        assert_eq!(msg.click.as_deref(), Some("https://backup.example.com"));
+        assert_eq!(msg.actions, received.actions);
+
+        let plain = decode(r#"{"id":"b","time":2,"event":"message","topic":"t","message":"hi"}"#)
+            .unwrap()
+            .unwrap();
+        let msg = crate::Message::try_from(&plain).unwrap();
+        assert_eq!(msg, crate::Message::builder("hi").build().unwrap());
+    }
+
+    #[test]
+    fn rejects_republishing_binary_bodies() {
+        let binary = decode(
+            r#"{"id":"a","time":1,"event":"message","topic":"t","message":"/wD+","encoding":"base64"}"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        let res = crate::Message::from_received(&binary);
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("binary")));
+    }
+}
@@ -0,0 +1,139 @@
+use reqwest::header::{HeaderMap, AUTHORIZATION, COOKIE, LOCATION, PROXY_AUTHORIZATION};
+use reqwest::{Method, StatusCode, Url};
+
+/// How redirects of the ntfy server are followed, e.g. from an old domain to a new one.
+///
+/// Redirects are followed by resending the request with the same method, headers and body, so a
+/// publish stays a publish on `301`, `302`, `307` and `308`. `303 See Other` is only followed
+/// for `GET` requests. Credentials are dropped when a redirect leads to another origin, so they
+/// don't leak, which makes authenticated requests fail there.
+///
+/// Redirects that aren't followed, e.g. beyond the limit or of streamed uploads whose body
+/// can't be resent, are returned as `NtfyError::Server`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redirect {
+    /// Follows up to the given number of redirects to any host.
+    Follow(usize),
+
+    /// Follows up to the given number of redirects as long as they stay on the origin of the
+    /// request, keeping its credentials.
+    SameOrigin(usize),
+
+    /// Never follows redirects.
+    None,
+}
+
+impl Default for Redirect {
+    /// Follows up to 10 redirects like `reqwest`.
+    fn default() -> Self {
+        Redirect::Follow(10)
+    }
+}
+
+impl Redirect {
+    /// Returns the URL to follow the response with `status` and `headers` to, given that
+    /// `followed` redirects were already followed for the request with `method` to `url`.
+    pub(crate) fn target(
+        self,
+        method: &Method,
+        url: &Url,
+        status: StatusCode,
+        headers: &HeaderMap,
+        followed: usize,
+    ) -> Option<Url> {
+        let max = match self {
+            Redirect::Follow(max) | Redirect::SameOrigin(max) => max,
+            Redirect::None => return None,
+        };
+        let resend = matches!(status.as_u16(), 301 | 302 | 307 | 308)
+            || (status == StatusCode::SEE_OTHER && method == Method::GET);
+        if !resend || followed >= max {
+            return None;
+        }
+
+        let location = headers.get(LOCATION)?.to_str().ok()?;
+        let target = url.join(location).ok()?;
+        if matches!(self, Redirect::SameOrigin(_)) && target.origin() != url.origin() {
+            return None;
+        }
+
+        Some(target)
+    }
+}
+
+/// Removes the credentials from `headers` if the redirect from `from` to `to` leaves the origin.
+pub(crate) fn strip_credentials(headers: &mut HeaderMap, from: &Url, to: &Url) {
+    if from.origin() != to.origin() {
+        for name in [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE] {
+            headers.remove(name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(target: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(LOCATION, target.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn keeps_the_method_of_redirected_publishes() {
+        let url = Url::parse("https://old.example.com/backup").unwrap();
+        let headers = location("/v2/backup");
+
+        for status in [301, 302, 307, 308] {
+            let status = StatusCode::from_u16(status).unwrap();
+            let target = Redirect::default().target(&Method::POST, &url, status, &headers, 0);
+            assert_eq!(
+                target.unwrap().as_str(),
+                "https://old.example.com/v2/backup"
+            );
+        }
+        let see_other = StatusCode::SEE_OTHER;
+        assert!(Redirect::default()
+            .target(&Method::POST, &url, see_other, &headers, 0)
+            .is_none());
+        assert!(Redirect::default()
+            .target(&Method::GET, &url, see_other, &headers, 0)
+            .is_some());
+    }
+
+    #[test]
+    fn limits_the_followed_redirects() {
+        let url = Url::parse("https://old.example.com/backup").unwrap();
+        let headers = location("https://new.example.com/backup");
+        let status = StatusCode::MOVED_PERMANENTLY;
+
+        let follow = Redirect::Follow(2);
+        assert!(follow
+            .target(&Method::POST, &url, status, &headers, 1)
+            .is_some());
+        assert!(follow
+            .target(&Method::POST, &url, status, &headers, 2)
+            .is_none());
+        let same_origin = Redirect::SameOrigin(2);
+        assert!(same_origin
+            .target(&Method::POST, &url, status, &headers, 0)
+            .is_none());
+        assert!(Redirect::None
+            .target(&Method::POST, &url, status, &headers, 0)
+            .is_none());
+    }
+
+    #[test]
+    fn strips_credentials_when_leaving_the_origin() {
+        let from = Url::parse("https://old.example.com/backup").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, "Bearer tk_secret".parse().unwrap());
+
+        strip_credentials(&mut headers, &from, &from.join("/v2/backup").unwrap());
+        assert!(headers.contains_key(AUTHORIZATION));
+        let other = Url::parse("https://new.example.com/backup").unwrap();
+        strip_credentials(&mut headers, &from, &other);
+        assert!(!headers.contains_key(AUTHORIZATION));
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use crate::{ntfy, Client, Message, NtfyError, PublishResponse, Topic};
+
+/// Publishes to several topics of one server, each with its own credentials.
+///
+/// All topics share the server, HTTP client and settings of the `Client` the registry is
+/// constructed from, only the topic and credentials differ.
+#[derive(Debug, Clone)]
+pub struct TopicRegistry {
+    client: Client,
+    topics: HashMap<String, Client>,
+}
+
+impl TopicRegistry {
+    /// Constructs an empty registry publishing via `client`.
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            topics: HashMap::new(),
+        }
+    }
+
+    /// Registers `topic` without credentials.
+    pub fn topic(self, topic: &str) -> Self {
+        self.register(topic, None, None)
+    }
+
+    /// Registers `topic` authenticating with an access token.
+    pub fn token(self, topic: &str, token: &str) -> Self {
+        self.register(topic, Some(token.to_string()), None)
+    }
+
+    /// Registers `topic` authenticating with a username and password.
+    pub fn basic_auth(self, topic: &str, username: &str, password: &str) -> Self {
+        let credentials = (username.to_string(), password.to_string());
+        self.register(topic, None, Some(credentials))
+    }
+
+    fn register(
+        mut self,
+        topic: &str,
+        token: Option<String>,
+        basic_auth: Option<(String, String)>,
+    ) -> Self {
+        let client = Client {
+            topic: Topic::new(topic),
+            token,
+            basic_auth,
+            ..self.client.clone()
+        };
+        self.topics.insert(topic.to_string(), client);
+        self
+    }
+
+    /// Returns the client of a registered topic.
+    pub fn client(&self, topic: &str) -> Option<&Client> {
+        self.topics.get(topic)
+    }
+
+    /// Publishes `msg` to `topic` with its registered credentials.
+    ///
+    /// Fails with `NtfyError::InvalidInput` if `topic` isn't registered.
+    pub async fn publish(&self, topic: &str, msg: Message) -> Result<PublishResponse, NtfyError> {
+        let cli = self
+            .client(topic)
+            .ok_or_else(|| NtfyError::InvalidInput(format!("topic `{topic}` is not registered")))?;
+        ntfy(cli, msg).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_server::{response, TestServer, PUBLISHED};
+
+    #[tokio::test]
+    async fn publishes_with_the_credentials_of_each_topic() {
+        let server = TestServer::start(vec![response(200, &[], PUBLISHED)]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("default")
+            .build()
+            .unwrap();
+        let registry = TopicRegistry::new(cli)
+            .token("backups", "tk_backups")
+            .basic_auth("alerts", "admin", "secret")
+            .topic("public");
+
+        for topic in ["backups", "alerts", "public"] {
+            let msg = Message::builder("hi").build().unwrap();
+            registry.publish(topic, msg).await.unwrap();
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/backups");
+        assert_eq!(
+            requests[0].header("Authorization"),
+            Some("Bearer tk_backups")
+        );
+        assert_eq!(requests[1].path, "/alerts");
+        assert_eq!(
+            requests[1].header("Authorization"),
+            Some("Basic YWRtaW46c2VjcmV0")
+        );
+        assert_eq!(requests[2].path, "/public");
+        assert_eq!(requests[2].header("Authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_topics() {
+        let cli = Client::builder()
+            .server("http://127.0.0.1:9")
+            .topic("default")
+            .build()
+            .unwrap();
+        let registry = TopicRegistry::new(cli).token("backups", "tk_backups");
+
+        let res = registry
+            .publish("alerts", Message::builder("hi").build().unwrap())
+            .await;
+
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("`alerts`")));
+        assert!(registry.client("alerts").is_none());
+    }
+}
@@ -0,0 +1,396 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+use crate::{Client, NtfyError, RequestContext};
+
+/// The number of attempts it took to deliver a notification, stored in the extensions of the
+/// response returned by `ntfy_raw`, e.g. `res.extensions().get::<Attempts>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attempts(pub u32);
+
+/// An opt-in policy for retrying notifications that failed for transient reasons.
+///
+/// Only connection errors, connections reset while sending the request and `429`, `502`, `503`
+/// and `504` responses are retried, using an exponential backoff with jitter or the
+/// `Retry-After` header sent by the server. A `Retry-After` longer than the `max_delay` is
+/// clamped to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub(crate) max_attempts: u32,
+
+    /// The delay before the first retry, doubled for every further retry.
+    base_delay: Duration,
+
+    /// The upper bound of the delay between two attempts.
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Constructs a new `RetryPolicy` making at most `max_attempts` attempts, waiting 500ms
+    /// before the first retry and at most 30s between two attempts.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn base_delay(self, base_delay: Duration) -> Self {
+        Self { base_delay, ..self }
+    }
+
+    /// Sets the upper bound of the delay between two attempts, including delays the server asks
+    /// for via `Retry-After`.
+    pub fn max_delay(self, max_delay: Duration) -> Self {
+        Self { max_delay, ..self }
+    }
+
+    /// Returns the backoff before the given retry (starting at 1), randomized between 50% and
+    /// 100% of the exponential delay.
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry - 1))
+            .min(self.max_delay);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        exp / 2 + exp.mul_f64(f64::from(nanos % 1000) / 2000.0)
+    }
+}
+
+/// Sends a prepared request, retrying transient failures according to the policy of `cli`.
+///
+/// Errors after more than one attempt are wrapped in `NtfyError::Retry`.
+pub(crate) async fn send(
+    cli: &Client,
+    req: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, NtfyError> {
+    let send = attempt(cli, req);
+    // the uuid is left out as it's a secret
+    #[cfg(feature = "tracing")]
+    let send = tracing::Instrument::instrument(
+        send,
+        tracing::info_span!("ntfy", server = %cli.server, topic = cli.topic.name()),
+    );
+
+    send.await
+}
+
+async fn attempt(
+    cli: &Client,
+    req: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, NtfyError> {
+    let policy = match &cli.retry {
+        Some(policy) => policy,
+        None => {
+            throttle(cli).await;
+            return crate::send(cli, req).await;
+        }
+    };
+
+    let mut attempt = 1;
+    loop {
+        throttle(cli).await;
+        // requests with streaming bodies can't be cloned and are only sent once
+        let this = match req.try_clone() {
+            Some(this) if attempt < policy.max_attempts => this,
+            _ => {
+                return match crate::send(cli, req).await {
+                    Ok(mut res) => {
+                        res.extensions_mut().insert(Attempts(attempt));
+                        Ok(res)
+                    }
+                    Err(e) => Err(wrap(e, attempt)),
+                }
+            }
+        };
+
+        let this = this.build()?;
+        let context = RequestContext::new(cli, this.method(), this.url());
+        let delay = match crate::execute(cli, this).await {
+            Ok(mut res) if res.status().is_success() => {
+                res.extensions_mut().insert(Attempts(attempt));
+                return Ok(res);
+            }
+            Ok(res) if is_retryable(res.status()) => {
+                retry_after(res.headers()).unwrap_or_else(|| policy.backoff(attempt))
+            }
+            Ok(res) => {
+                let e = NtfyError::from_response(res).await.with_context(&context);
+                return Err(wrap(e, attempt));
+            }
+            Err(NtfyError::Connect { .. }) => policy.backoff(attempt),
+            Err(NtfyError::Request { source, .. }) if source.is_request() => {
+                policy.backoff(attempt)
+            }
+            Err(e) => return Err(wrap(e, attempt)),
+        };
+
+        let delay = delay.min(policy.max_delay);
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            "retrying notification"
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Waits for the rate limiter of `cli`, if any.
+async fn throttle(cli: &Client) {
+    if let Some(limiter) = &cli.rate_limiter {
+        limiter.acquire().await;
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Parses the `Retry-After` header given either in seconds or as HTTP date, e.g.
+/// `Wed, 21 Oct 2015 07:28:00 GMT`. Dates in the past result in a zero duration.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+fn wrap(e: NtfyError, attempts: u32) -> NtfyError {
+    if attempts == 1 {
+        return e;
+    }
+
+    NtfyError::Retry {
+        attempts,
+        source: Box::new(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Instant;
+
+    use crate::test_server::{response, TestServer, PUBLISHED};
+    use crate::{ntfy, Message};
+
+    fn client(url: &str, policy: RetryPolicy) -> Client {
+        Client::builder()
+            .server(url)
+            .topic("test")
+            .uuid("uuid")
+            .retry(policy)
+            .build()
+            .unwrap()
+    }
+
+    fn fast(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).base_delay(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let server = TestServer::start(vec![
+            response(503, &[], "unavailable"),
+            response(503, &[], "unavailable"),
+            response(200, &[], PUBLISHED),
+        ])
+        .await;
+        let cli = client(&server.url, fast(5));
+
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.attempts, 3);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|req| req.body == b"hi"));
+    }
+
+    #[tokio::test]
+    async fn fails_immediately_on_client_errors() {
+        let server = TestServer::start(vec![response(400, &[], "bad request")]).await;
+        let cli = client(&server.url, fast(5));
+
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap()).await;
+
+        assert!(matches!(res, Err(NtfyError::Server { status: 400, .. })));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn wraps_the_last_error_once_exhausted() {
+        let server = TestServer::start(vec![response(502, &[], "bad gateway")]).await;
+        let cli = client(&server.url, fast(3));
+
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap()).await;
+
+        match res {
+            Err(NtfyError::Retry { attempts, source }) => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(*source, NtfyError::Server { status: 502, .. }));
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn clamps_retry_after_to_the_max_delay() {
+        let server = TestServer::start(vec![
+            response(429, &[("Retry-After", "3600")], "slow down"),
+            response(200, &[], PUBLISHED),
+        ])
+        .await;
+        let cli = client(&server.url, fast(2).max_delay(Duration::from_millis(10)));
+
+        let start = Instant::now();
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn backs_off_exponentially_with_jitter() {
+        let policy = RetryPolicy::new(5)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(300));
+
+        for (retry, exp) in [(1, 100), (2, 200), (3, 300), (4, 300)] {
+            let delay = policy.backoff(retry);
+            let exp = Duration::from_millis(exp);
+            assert!(
+                delay >= exp / 2 && delay <= exp,
+                "{delay:?} for retry {retry}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let header = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(reqwest::header::RETRY_AFTER, value.parse().unwrap());
+            retry_after(&headers)
+        };
+        let in_a_minute = (chrono::Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+
+        assert_eq!(header("30"), Some(Duration::from_secs(30)));
+        assert_eq!(header(" 5 "), Some(Duration::from_secs(5)));
+        assert_eq!(
+            header("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        let delay = header(&in_a_minute).unwrap();
+        assert!(delay > Duration::from_secs(50) && delay <= Duration::from_secs(60));
+        assert_eq!(header("soon"), None);
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn records_attempts_of_the_last_try() {
+        let server = TestServer::start(vec![
+            response(503, &[], "unavailable"),
+            response(200, &[], PUBLISHED),
+        ])
+        .await;
+        let cli = client(&server.url, fast(2));
+
+        let res = crate::ntfy_raw(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.extensions().get::<Attempts>(), Some(&Attempts(2)));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn emits_events_for_retries() {
+        use std::sync::{Arc, Mutex};
+
+        /// Records the messages of the events emitted by this crate.
+        #[derive(Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+                metadata.target().starts_with("ntfy_rs")
+            }
+            fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+                tracing::span::Id::from_u64(1)
+            }
+            fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+            fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+            fn event(&self, event: &tracing::Event<'_>) {
+                struct Message<'a>(&'a mut Vec<String>);
+                impl tracing::field::Visit for Message<'_> {
+                    fn record_debug(
+                        &mut self,
+                        field: &tracing::field::Field,
+                        value: &dyn std::fmt::Debug,
+                    ) {
+                        if field.name() == "message" {
+                            self.0.push(format!("{value:?}"));
+                        }
+                    }
+                }
+                event.record(&mut Message(&mut self.0.lock().unwrap()));
+            }
+            fn enter(&self, _: &tracing::span::Id) {}
+            fn exit(&self, _: &tracing::span::Id) {}
+        }
+
+        let server = TestServer::start(vec![
+            response(503, &[], "unavailable"),
+            response(200, &[], PUBLISHED),
+        ])
+        .await;
+        let cli = client(&server.url, fast(2));
+        let recorder = Recorder::default();
+        let messages = recorder.0.clone();
+
+        let _guard = tracing::subscriber::set_default(recorder);
+        ntfy(&cli, Message::builder("hi").build().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *messages.lock().unwrap(),
+            [
+                "request completed",
+                "retrying notification",
+                "request completed"
+            ]
+        );
+    }
+}
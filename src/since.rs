@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use crate::{MessageId, PublishResponse};
+
+/// Selects which cached messages are returned when subscribing or polling, see
+/// [fetching cached messages](https://docs.ntfy.sh/subscribe/api/#fetch-cached-messages).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Since {
+    /// Messages published within the given duration, e.g. the last 10 minutes.
+    Duration(Duration),
+
+    /// Messages published after the given Unix timestamp.
+    Timestamp(i64),
+
+    /// All cached messages.
+    All,
+
+    /// Messages published after the message with the given id.
+    Id(MessageId),
+}
+
+impl Since {
+    /// Returns the value of the `since` query parameter.
+    pub(crate) fn query_value(&self) -> String {
+        match self {
+            Since::Duration(duration) => format!("{}s", duration.as_secs()),
+            Since::Timestamp(timestamp) => timestamp.to_string(),
+            Since::All => "all".to_string(),
+            Since::Id(id) => id.to_string(),
+        }
+    }
+}
+
+/// Selects the messages published after a message sent with `ntfy`, e.g. to poll for replies.
+impl From<&PublishResponse> for Since {
+    fn from(res: &PublishResponse) -> Self {
+        Since::Id(res.message_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_query_values() {
+        let cases = [
+            (Since::Duration(Duration::from_secs(600)), "600s"),
+            (Since::Timestamp(1645970742), "1645970742"),
+            (Since::All, "all"),
+            (Since::Id("nFS3knfcQ1xe".into()), "nFS3knfcQ1xe"),
+        ];
+        for (since, expected) in cases {
+            assert_eq!(since.query_value(), expected);
+        }
+    }
+}
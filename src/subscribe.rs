@@ -0,0 +1,671 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures_util::stream::{self, AbortHandle, Stream, StreamExt};
+
+use crate::{
+    authorize, poll, received, send, Client, NtfyError, ReceivedMessage, RetryPolicy, Since,
+    SubscribeFilter,
+};
+
+/// Subscribes to the topic of `cli` via [server-sent events](https://docs.ntfy.sh/subscribe/api/#subscribe-as-sse-stream)
+/// and returns a stream of the received messages.
+///
+/// The token or basic auth credentials of `cli` are sent along to read protected topics.
+///
+/// Only messages matching `filter` are delivered. `open` and `keepalive` events are skipped,
+/// the stream ends when the server closes the connection.
+pub async fn subscribe(
+    cli: &Client,
+    filter: &SubscribeFilter,
+) -> Result<impl Stream<Item = Result<ReceivedMessage, NtfyError>>, NtfyError> {
+    let res = open(cli, filter).await?;
+    Ok(parse_sse(watch(
+        res.bytes_stream(),
+        filter.keepalive_timeout,
+    )))
+}
+
+/// Subscribes like [`subscribe`], but first delivers the cached messages selected by `since`
+/// and then follows the topic live in the same stream.
+///
+/// The cached messages are polled first, the live subscription then continues after the last
+/// of them, so no message is delivered twice.
+pub async fn subscribe_since(
+    cli: &Client,
+    filter: &SubscribeFilter,
+    since: Since,
+) -> Result<impl Stream<Item = Result<ReceivedMessage, NtfyError>>, NtfyError> {
+    let history = poll(cli, &filter.clone().since(since.clone())).await?;
+    let last = history.last().map(|msg| msg.id.clone());
+    let live = match &last {
+        Some(id) => filter.clone().since(Since::Id(id.into())),
+        None => filter.clone().since(since),
+    };
+    let live = subscribe(cli, &live).await?.filter(move |res| {
+        let duplicate = matches!((res, &last), (Ok(msg), Some(id)) if &msg.id == id);
+        std::future::ready(!duplicate)
+    });
+
+    Ok(stream::iter(history.into_iter().map(Ok)).chain(live))
+}
+
+/// Subscribes like [`subscribe`], but also returns a `SubscribeHandle` to close the subscription
+/// from elsewhere, e.g. on shutdown.
+pub async fn subscribe_with_handle(
+    cli: &Client,
+    filter: &SubscribeFilter,
+) -> Result<
+    (
+        impl Stream<Item = Result<ReceivedMessage, NtfyError>>,
+        SubscribeHandle,
+    ),
+    NtfyError,
+> {
+    let (messages, handle) = stream::abortable(subscribe(cli, filter).await?);
+    Ok((messages, SubscribeHandle(handle)))
+}
+
+/// Closes a subscription created by [`subscribe_with_handle`].
+///
+/// Clones close the same subscription. Dropping the handle leaves the subscription open.
+#[derive(Debug, Clone)]
+pub struct SubscribeHandle(AbortHandle);
+
+impl SubscribeHandle {
+    /// Closes the subscription, so its stream ends even while it waits for the next message.
+    /// The connection is closed once the stream is dropped.
+    pub fn close(&self) {
+        self.0.abort();
+    }
+
+    /// Returns whether `close` has been called.
+    pub fn is_closed(&self) -> bool {
+        self.0.is_aborted()
+    }
+}
+
+/// Opens the SSE connection to the topic of `cli`.
+async fn open(cli: &Client, filter: &SubscribeFilter) -> Result<reqwest::Response, NtfyError> {
+    let url = filter.url(cli, "/sse")?;
+    send(cli, authorize(cli, cli.http_client.get(url))?).await
+}
+
+/// An event of a subscription created by [`subscribe_reconnecting`].
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum SubscribeEvent {
+    /// A message was received.
+    Message(ReceivedMessage),
+
+    /// The connection was lost or couldn't be established and is retried after `delay`.
+    Reconnecting {
+        /// The number of consecutive reconnection attempts, starting at 1.
+        attempt: u32,
+
+        /// The delay before the next attempt.
+        delay: Duration,
+
+        /// Why the connection was lost, `None` if the server closed it.
+        cause: Option<NtfyError>,
+    },
+}
+
+/// Subscribes like [`subscribe`], but transparently reconnects according to `policy` when the
+/// connection drops.
+///
+/// Reconnections resume after the last received message, or from the time of the first
+/// connection if none was received yet, so no messages are lost or delivered twice. Every
+/// reconnection is reported as `SubscribeEvent::Reconnecting`, the stream ends with the last
+/// error once `policy` allows no further attempts. The attempts are reset whenever a `message`
+/// event is received.
+pub fn subscribe_reconnecting(
+    cli: &Client,
+    filter: &SubscribeFilter,
+    policy: RetryPolicy,
+) -> impl Stream<Item = Result<SubscribeEvent, NtfyError>> {
+    let state = Reconnect {
+        cli: cli.clone(),
+        filter: filter.clone(),
+        policy,
+        attempt: 0,
+        delay: None,
+        messages: None,
+        done: false,
+    };
+    stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        let item = state.next().await?;
+        Some((item, state))
+    })
+}
+
+/// The messages received over a single connection.
+type Messages = Pin<Box<dyn Stream<Item = Result<ReceivedMessage, NtfyError>> + Send>>;
+
+/// The state of a reconnecting subscription.
+struct Reconnect {
+    cli: Client,
+
+    /// The filter, whose `since` is replaced by the id of the last message or, until a message
+    /// arrives, the time of the first connection.
+    filter: SubscribeFilter,
+
+    policy: RetryPolicy,
+
+    /// The number of consecutive failed connections, reset once a message arrives.
+    attempt: u32,
+
+    /// The delay to wait for before connecting again.
+    delay: Option<Duration>,
+
+    /// The messages of the current connection, if connected.
+    messages: Option<Messages>,
+
+    /// Whether the stream has ended because no further attempts are allowed.
+    done: bool,
+}
+
+impl Reconnect {
+    async fn next(&mut self) -> Option<Result<SubscribeEvent, NtfyError>> {
+        loop {
+            let cause = match &mut self.messages {
+                Some(messages) => match messages.next().await {
+                    Some(Ok(msg)) => {
+                        if msg.event == "message" {
+                            self.filter = self.filter.clone().since(Since::Id(msg.message_id()));
+                            self.attempt = 0;
+                        }
+                        return Some(Ok(SubscribeEvent::Message(msg)));
+                    }
+                    Some(Err(e @ NtfyError::Decode(_))) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %e, "dropped a message that couldn't be decoded");
+                        return Some(Err(e));
+                    }
+                    Some(Err(e)) => Some(e),
+                    None => None,
+                },
+                None => {
+                    if let Some(delay) = self.delay.take() {
+                        tokio::time::sleep(delay).await;
+                    }
+                    let connected_at = chrono::Utc::now().timestamp();
+                    match open(&self.cli, &self.filter).await {
+                        Ok(res) => {
+                            // until a message arrives, resume from the first connection so
+                            // messages published while reconnecting aren't missed
+                            self.filter =
+                                self.filter.clone().since_or(Since::Timestamp(connected_at));
+                            let bytes = watch(res.bytes_stream(), self.filter.keepalive_timeout);
+                            self.messages = Some(Box::pin(parse_sse(bytes)));
+                            continue;
+                        }
+                        Err(e) => Some(e),
+                    }
+                }
+            };
+
+            self.messages = None;
+            self.attempt += 1;
+            if self.attempt >= self.policy.max_attempts {
+                self.done = true;
+                return cause.map(Err);
+            }
+
+            let delay = self.policy.backoff(self.attempt);
+            self.delay = Some(delay);
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                server = %self.cli.server,
+                topic = self.cli.topic.name(),
+                attempt = self.attempt,
+                delay_ms = delay.as_millis() as u64,
+                cause = cause.as_ref().map(tracing::field::display),
+                "subscription lost, reconnecting"
+            );
+            return Some(Ok(SubscribeEvent::Reconnecting {
+                attempt: self.attempt,
+                delay,
+                cause,
+            }));
+        }
+    }
+}
+
+/// The raw bytes received over a single connection.
+type Bytes = Pin<Box<dyn Stream<Item = Result<bytes::Bytes, NtfyError>> + Send>>;
+
+/// Ends `bytes` with `NtfyError::Elapsed` if nothing arrives within `timeout`.
+fn watch<S>(bytes: S, timeout: Option<Duration>) -> Bytes
+where
+    S: Stream<Item = reqwest::Result<bytes::Bytes>> + Send + Unpin + 'static,
+{
+    let bytes = bytes.map(|chunk| chunk.map_err(NtfyError::from));
+    let Some(timeout) = timeout else {
+        return Box::pin(bytes);
+    };
+
+    Box::pin(stream::unfold(Some(bytes), move |bytes| async move {
+        let mut bytes = bytes?;
+        match tokio::time::timeout(timeout, bytes.next()).await {
+            Ok(Some(chunk)) => Some((chunk, Some(bytes))),
+            Ok(None) => None,
+            Err(_) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    timeout_ms = timeout.as_millis() as u64,
+                    "no keepalive received, connection considered dead"
+                );
+                Some((Err(NtfyError::Elapsed { timeout }), None))
+            }
+        }
+    }))
+}
+
+/// Parses a `text/event-stream` byte stream into received messages.
+pub(crate) fn parse_sse<S, B, E>(bytes: S) -> impl Stream<Item = Result<ReceivedMessage, NtfyError>>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Into<NtfyError>,
+{
+    let state = (bytes, SseParser::default(), VecDeque::new());
+    stream::unfold(state, |(mut bytes, mut parser, mut pending)| async move {
+        loop {
+            if let Some(item) = pending.pop_front() {
+                return Some((item, (bytes, parser, pending)));
+            }
+
+            match bytes.next().await {
+                Some(Ok(chunk)) => pending.extend(parser.feed(chunk.as_ref())),
+                Some(Err(e)) => return Some((Err(e.into()), (bytes, parser, pending))),
+                None => return None,
+            }
+        }
+    })
+}
+
+/// An incremental parser for `text/event-stream` frames.
+#[derive(Debug, Default)]
+struct SseParser {
+    /// Bytes of a line that hasn't been terminated yet.
+    line: Vec<u8>,
+
+    /// The `event:` field of the current frame.
+    event: Option<String>,
+
+    /// The `data:` lines of the current frame.
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feeds a chunk of bytes into the parser and returns the messages of all completed frames.
+    fn feed(&mut self, chunk: &[u8]) -> Vec<Result<ReceivedMessage, NtfyError>> {
+        let mut messages = Vec::new();
+        for &byte in chunk {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+
+            let line = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(&line);
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            if line.is_empty() {
+                messages.extend(self.dispatch());
+            } else if let Some(event) = field(line, "event") {
+                self.event = Some(event.to_string());
+            } else if let Some(data) = field(line, "data") {
+                self.data.push(data.to_string());
+            }
+        }
+
+        messages
+    }
+
+    /// Finishes the current frame, returning its message unless it's an `open` or `keepalive` event.
+    fn dispatch(&mut self) -> Option<Result<ReceivedMessage, NtfyError>> {
+        let event = self.event.take();
+        let data = std::mem::take(&mut self.data);
+        if data.is_empty() || event.as_deref().is_some_and(received::is_control) {
+            return None;
+        }
+
+        received::decode(&data.join("\n"))
+    }
+}
+
+/// Returns the value of `line` if it's the given SSE field.
+fn field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let value = line.strip_prefix(name)?.strip_prefix(':')?;
+    Some(value.strip_prefix(' ').unwrap_or(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn parses_canned_stream() {
+        let chunks: Vec<Result<&[u8], NtfyError>> = vec![
+            Ok(b"event: open\ndata: {\"id\":\"a\",\"time\":1,\"event\":\"open\",\"topic\":\"t\"}\n\n"),
+            Ok(b"event: keepalive\ndata: {\"id\":\"b\",\"time\":2,\"event\":\"keepalive\",\"topic\":\"t\"}\n\n"),
+            Ok(b"event: message\r\ndata: {\"id\":\"c\",\"time\":3,\"event\":\"message\","),
+            Ok(b"\"topic\":\"t\",\"message\":\"hi\",\"title\":\"Hello\"}\r\n\r\n"),
+            Ok(b": comment\ndata: {\"id\":\"d\",\"time\":4,\"event\":\"message\",\"topic\":\"t\",\"message\":\"bye\"}\n\n"),
+        ];
+        let received: Vec<_> = parse_sse(stream::iter(chunks)).collect().await;
+
+        let messages: Vec<_> = received.into_iter().map(Result::unwrap).collect();
+        let ids: Vec<_> = messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["c", "d"]);
+        assert_eq!(messages[0].message.as_deref(), Some("hi"));
+        assert_eq!(messages[0].title.as_deref(), Some("Hello"));
+        assert_eq!(messages[1].message.as_deref(), Some("bye"));
+        assert_eq!(messages[1].title, None);
+    }
+
+    #[tokio::test]
+    async fn yields_decode_errors() {
+        let chunks: Vec<Result<&[u8], NtfyError>> = vec![Ok(b"data: not json\n\n")];
+        let received: Vec<_> = parse_sse(stream::iter(chunks)).collect().await;
+
+        assert!(matches!(received[..], [Err(NtfyError::Decode(_))]));
+    }
+
+    fn sse(events: &[&str]) -> String {
+        let body: String = events
+            .iter()
+            .map(|data| {
+                let event = if data.contains("\"open\"") {
+                    "open"
+                } else {
+                    "message"
+                };
+                format!("event: {event}\ndata: {data}\n\n")
+            })
+            .collect();
+        crate::test_server::response(200, &[("Content-Type", "text/event-stream")], &body)
+    }
+
+    fn fast(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).base_delay(Duration::from_millis(1))
+    }
+
+    const OPEN: &str = r#"{"id":"o","time":1,"event":"open","topic":"test_uuid"}"#;
+
+    #[tokio::test]
+    async fn resumes_after_the_last_message() {
+        use crate::test_server::TestServer;
+
+        let server = TestServer::start(vec![
+            sse(&[
+                OPEN,
+                r#"{"id":"m1","time":2,"event":"message","topic":"test_uuid","message":"one"}"#,
+                r#"{"id":"m2","time":3,"event":"message","topic":"test_uuid","message":"two"}"#,
+            ]),
+            sse(&[
+                OPEN,
+                r#"{"id":"m3","time":4,"event":"message","topic":"test_uuid","message":"three"}"#,
+            ]),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let events: Vec<_> = subscribe_reconnecting(&cli, &SubscribeFilter::new(), fast(3))
+            .take(4)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        let ids: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                SubscribeEvent::Message(msg) => Some(msg.id.as_str()),
+                SubscribeEvent::Reconnecting { .. } => None,
+            })
+            .collect();
+        assert_eq!(ids, ["m1", "m2", "m3"]);
+        assert!(matches!(
+            events[2],
+            SubscribeEvent::Reconnecting {
+                attempt: 1,
+                cause: None,
+                ..
+            }
+        ));
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/test_uuid/sse");
+        assert_eq!(requests[1].path, "/test_uuid/sse?since=m2");
+    }
+
+    #[tokio::test]
+    async fn resumes_from_the_connect_time_until_a_message_arrives() {
+        use crate::test_server::TestServer;
+
+        let server = TestServer::start(vec![sse(&[OPEN])]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let before = chrono::Utc::now().timestamp();
+        let events: Vec<_> = subscribe_reconnecting(&cli, &SubscribeFilter::new(), fast(3))
+            .collect()
+            .await;
+
+        // `open` events don't reset the attempts, so the stream ends after three connections
+        let attempts: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                Ok(SubscribeEvent::Reconnecting { attempt, .. }) => *attempt,
+                event => panic!("unexpected event: {event:?}"),
+            })
+            .collect();
+        assert_eq!(attempts, [1, 2]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        let since: i64 = requests[1]
+            .path
+            .strip_prefix("/test_uuid/sse?since=")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(since >= before && since <= chrono::Utc::now().timestamp());
+        assert_eq!(requests[2].path, requests[1].path);
+    }
+
+    #[tokio::test]
+    async fn keeps_an_explicit_since_until_a_message_arrives() {
+        use crate::test_server::TestServer;
+
+        let server = TestServer::start(vec![sse(&[OPEN])]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let filter = SubscribeFilter::new().since(Since::All);
+        let _: Vec<_> = subscribe_reconnecting(&cli, &filter, fast(2))
+            .collect()
+            .await;
+
+        let requests = server.requests();
+        assert_eq!(requests[1].path, "/test_uuid/sse?since=all");
+    }
+
+    #[tokio::test]
+    async fn subscribes_with_auth() {
+        use crate::test_server::TestServer;
+
+        let server = TestServer::start(vec![sse(&[OPEN])]).await;
+        let cli = Client::with_token(&server.url, "test", "uuid", "tk_secret").unwrap();
+
+        let messages: Vec<_> = subscribe(&cli, &SubscribeFilter::new())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert!(messages.is_empty());
+        let req = &server.requests()[0];
+        assert_eq!(req.header("Authorization"), Some("Bearer tk_secret"));
+    }
+
+    #[tokio::test]
+    async fn closes_subscriptions_via_the_handle() {
+        use crate::test_server::TestServer;
+
+        // announces a longer body than it sends, so the stream waits for more messages
+        let event = format!(
+            "event: message\ndata: {}\n\n",
+            r#"{"id":"m1","time":2,"event":"message","topic":"test_uuid","message":"one"}"#
+        );
+        let hanging = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: 100000\r\n\r\n{event}"
+        );
+        let server = TestServer::start(vec![hanging]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let (messages, handle) = subscribe_with_handle(&cli, &SubscribeFilter::new())
+            .await
+            .unwrap();
+        let mut messages = Box::pin(messages);
+        assert_eq!(messages.next().await.unwrap().unwrap().id, "m1");
+
+        let closer = handle.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            closer.close();
+        });
+        let next = tokio::time::timeout(Duration::from_secs(5), messages.next()).await;
+
+        assert!(matches!(next, Ok(None)));
+        assert!(handle.is_closed());
+    }
+
+    #[tokio::test]
+    async fn keeps_subscriptions_open_when_the_handle_is_dropped() {
+        use crate::test_server::TestServer;
+
+        let server = TestServer::start(vec![sse(&[
+            OPEN,
+            r#"{"id":"m1","time":2,"event":"message","topic":"test_uuid","message":"one"}"#,
+        ])])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let (messages, handle) = subscribe_with_handle(&cli, &SubscribeFilter::new())
+            .await
+            .unwrap();
+        drop(handle);
+        let ids: Vec<_> = messages.map(|msg| msg.unwrap().id).collect().await;
+
+        assert_eq!(ids, ["m1"]);
+    }
+
+    #[tokio::test]
+    async fn delivers_cached_messages_before_live_ones() {
+        use crate::test_server::{response, TestServer};
+
+        let h1 = r#"{"id":"h1","time":2,"event":"message","topic":"test_uuid","message":"one"}"#;
+        let h2 = r#"{"id":"h2","time":3,"event":"message","topic":"test_uuid","message":"two"}"#;
+        let live = r#"{"id":"l1","time":4,"event":"message","topic":"test_uuid","message":"live"}"#;
+        let server = TestServer::start(vec![
+            response(200, &[], &format!("{h1}\n{h2}\n")),
+            sse(&[OPEN, h2, live]),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let ids: Vec<_> = subscribe_since(&cli, &SubscribeFilter::new(), Since::All)
+            .await
+            .unwrap()
+            .map(|res| res.unwrap().id)
+            .collect()
+            .await;
+
+        assert_eq!(ids, ["h1", "h2", "l1"]);
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/test_uuid/json?since=all&poll=1");
+        assert_eq!(requests[1].path, "/test_uuid/sse?since=h2");
+    }
+
+    #[tokio::test]
+    async fn follows_from_since_without_cached_messages() {
+        use crate::test_server::{response, TestServer};
+
+        let server = TestServer::start(vec![response(200, &[], ""), sse(&[OPEN])]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let messages: Vec<_> = subscribe_since(&cli, &SubscribeFilter::new(), Since::All)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert!(messages.is_empty());
+        assert_eq!(server.requests()[1].path, "/test_uuid/sse?since=all");
+    }
+
+    /// A response that sends the `open` event and then stays silent.
+    fn silent() -> String {
+        let event = format!("event: open\ndata: {OPEN}\n\n");
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: 100000\r\n\r\n{event}"
+        )
+    }
+
+    #[tokio::test]
+    async fn ends_subscriptions_without_keepalives() {
+        use crate::test_server::TestServer;
+
+        let server = TestServer::start(vec![silent()]).await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let timeout = Duration::from_millis(100);
+        let filter = SubscribeFilter::new().keepalive_timeout(timeout);
+        let messages = subscribe(&cli, &filter).await.unwrap().collect::<Vec<_>>();
+        let messages = tokio::time::timeout(Duration::from_secs(5), messages)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            messages[..],
+            [Err(NtfyError::Elapsed { timeout: t })] if t == timeout
+        ));
+    }
+
+    #[tokio::test]
+    async fn reconnects_dead_subscriptions() {
+        use crate::test_server::TestServer;
+
+        let server = TestServer::start(vec![
+            silent(),
+            sse(&[
+                OPEN,
+                r#"{"id":"m1","time":2,"event":"message","topic":"test_uuid","message":"one"}"#,
+            ]),
+        ])
+        .await;
+        let cli = Client::new(&server.url, "test", "uuid").unwrap();
+
+        let filter = SubscribeFilter::new().keepalive_timeout(Duration::from_millis(100));
+        let events = subscribe_reconnecting(&cli, &filter, fast(3))
+            .take(2)
+            .collect::<Vec<_>>();
+        let events = tokio::time::timeout(Duration::from_secs(5), events)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            events[0],
+            Ok(SubscribeEvent::Reconnecting {
+                attempt: 1,
+                cause: Some(NtfyError::Elapsed { .. }),
+                ..
+            })
+        ));
+        assert!(matches!(&events[1], Ok(SubscribeEvent::Message(msg)) if msg.id == "m1"));
+        assert_eq!(server.requests().len(), 2);
+    }
+}
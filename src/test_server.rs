@@ -0,0 +1,178 @@
+//! A minimal HTTP/1.1 server on localhost for tests that need a real connection.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// The body ntfy responds with to a published message.
+pub(crate) const PUBLISHED: &str = r#"{"id":"sPs71M8A2T","time":1673542291,"expires":1673585491,"event":"message","topic":"test_uuid","message":"hi"}"#;
+
+/// A request received by a `TestServer`.
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Request {
+    /// Returns the value of the header `name`, compared case-insensitively.
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Serves canned responses and records the requests it receives.
+pub(crate) struct TestServer {
+    /// The base URL of the server, e.g. `http://127.0.0.1:1234`.
+    pub(crate) url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl TestServer {
+    /// Starts a server answering requests with `responses` in order, repeating the last one.
+    pub(crate) async fn start(responses: Vec<String>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let (recorded, accepted) = (requests.clone(), connections.clone());
+        let responses = Arc::new(responses);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(serve(stream, recorded.clone(), responses.clone()));
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            connections,
+        }
+    }
+
+    /// Returns all requests received so far.
+    pub(crate) fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the number of connections accepted so far.
+    pub(crate) fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+/// Builds a raw HTTP response with `status`, `headers` and `body`.
+pub(crate) fn response(status: u16, headers: &[(&str, &str)], body: &str) -> String {
+    let mut res = format!(
+        "HTTP/1.1 {status} Test\r\nContent-Length: {}\r\n",
+        body.len()
+    );
+    for (name, value) in headers {
+        res.push_str(&format!("{name}: {value}\r\n"));
+    }
+    res.push_str("\r\n");
+    res.push_str(body);
+    res
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    requests: Arc<Mutex<Vec<Request>>>,
+    responses: Arc<Vec<String>>,
+) {
+    let mut buf = Vec::new();
+    while let Some(req) = read_request(&mut stream, &mut buf).await {
+        let res = {
+            let mut requests = requests.lock().unwrap();
+            requests.push(req);
+            let idx = (requests.len() - 1).min(responses.len() - 1);
+            responses[idx].clone()
+        };
+        if stream.write_all(res.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Reads the next request from `stream`, `None` once the connection is closed.
+async fn read_request(stream: &mut TcpStream, buf: &mut Vec<u8>) -> Option<Request> {
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        fill(stream, buf).await?;
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    buf.drain(..head_end + 4);
+
+    let mut lines = head.split("\r\n");
+    let mut start = lines.next()?.split(' ');
+    let (method, path) = (start.next()?.to_string(), start.next()?.to_string());
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let mut req = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    if req
+        .header("Transfer-Encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+    {
+        loop {
+            let line_end = loop {
+                if let Some(pos) = buf.windows(2).position(|w| w == b"\r\n") {
+                    break pos;
+                }
+                fill(stream, buf).await?;
+            };
+            let size =
+                usize::from_str_radix(std::str::from_utf8(&buf[..line_end]).ok()?, 16).ok()?;
+            while buf.len() < line_end + 2 + size + 2 {
+                fill(stream, buf).await?;
+            }
+            req.body
+                .extend_from_slice(&buf[line_end + 2..line_end + 2 + size]);
+            buf.drain(..line_end + 2 + size + 2);
+            if size == 0 {
+                break;
+            }
+        }
+    } else {
+        let len: usize = req
+            .header("Content-Length")
+            .map_or(0, |v| v.parse().unwrap());
+        while buf.len() < len {
+            fill(stream, buf).await?;
+        }
+        req.body = buf.drain(..len).collect();
+    }
+
+    Some(req)
+}
+
+/// Reads more bytes from `stream` into `buf`, `None` once the connection is closed.
+async fn fill(stream: &mut TcpStream, buf: &mut Vec<u8>) -> Option<()> {
+    let mut chunk = [0; 4096];
+    match stream.read(&mut chunk).await {
+        Ok(0) | Err(_) => None,
+        Ok(n) => {
+            buf.extend_from_slice(&chunk[..n]);
+            Some(())
+        }
+    }
+}
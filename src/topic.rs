@@ -0,0 +1,130 @@
+use std::fmt;
+
+use crate::{validate, NtfyError};
+
+/// A topic which acts like a channel that receives notifications, optionally suffixed with a
+/// uuid (or another password like String) for privacy reasons.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Topic {
+    /// The name of the topic, e.g. `backup`.
+    name: String,
+
+    /// The uuid appended as `name_uuid`, no suffix is appended if empty.
+    suffix: String,
+}
+
+impl Topic {
+    /// Constructs a new `Topic` without a suffix.
+    pub fn new(name: &str) -> Self {
+        Self::with_suffix(name, "")
+    }
+
+    /// Constructs a new `Topic` suffixed with `uuid`, no suffix is appended if it's empty.
+    pub fn with_suffix(name: &str, uuid: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            suffix: uuid.to_string(),
+        }
+    }
+
+    /// Returns the name of the topic without the suffix.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the uuid suffix, if any.
+    pub fn suffix(&self) -> Option<&str> {
+        Some(self.suffix.as_str()).filter(|suffix| !suffix.is_empty())
+    }
+
+    /// Returns the topic as used in URLs, i.e. `name_uuid` or `name` without a suffix.
+    pub fn path_segment(&self) -> String {
+        match self.suffix() {
+            Some(suffix) => format!("{}_{suffix}", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Replaces the uuid suffix of the topic in `text`, e.g. a URL, with `***`.
+    pub(crate) fn redact(&self, text: &str) -> String {
+        match self.suffix() {
+            Some(_) => text.replace(&self.path_segment(), &format!("{}_***", self.name)),
+            None => text.to_string(),
+        }
+    }
+
+    /// Checks that the name and suffix only consist of `[-_A-Za-z0-9]` and that the path segment
+    /// is at most 64 characters long, as required by ntfy.
+    pub fn validate(&self) -> Result<(), NtfyError> {
+        validate::topic(&self.name, &self.suffix)
+    }
+}
+
+impl From<&str> for Topic {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl fmt::Debug for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Topic")
+            .field("name", &self.name)
+            .field("suffix", &self.suffix().map(|_| "***"))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_path_segment() {
+        assert_eq!(Topic::new("backup").path_segment(), "backup");
+        assert_eq!(Topic::with_suffix("backup", "").path_segment(), "backup");
+        assert_eq!(
+            Topic::with_suffix("backup", "8fd2").path_segment(),
+            "backup_8fd2"
+        );
+        assert_eq!(Topic::from("backup"), Topic::new("backup"));
+    }
+
+    #[test]
+    fn exposes_name_and_suffix() {
+        let topic = Topic::with_suffix("backup", "8fd2");
+        assert_eq!(topic.name(), "backup");
+        assert_eq!(topic.suffix(), Some("8fd2"));
+        assert_eq!(Topic::new("backup").suffix(), None);
+    }
+
+    #[test]
+    fn validates_name_and_suffix() {
+        assert!(Topic::with_suffix("backup", "8fd2").validate().is_ok());
+        assert!(Topic::new("back up").validate().is_err());
+        assert!(Topic::with_suffix("backup", "8fd2/../x")
+            .validate()
+            .is_err());
+        assert!(Topic::new("").validate().is_err());
+        assert!(Topic::with_suffix("backup", &"a".repeat(64))
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn masks_the_suffix_in_debug_output() {
+        let output = format!("{:?}", Topic::with_suffix("backup", "8fd2secret"));
+        assert!(!output.contains("8fd2secret"), "{output}");
+        assert!(output.contains("backup"), "{output}");
+    }
+
+    #[test]
+    fn redacts_the_suffix_in_text() {
+        let topic = Topic::with_suffix("backup", "8fd2");
+        assert_eq!(
+            topic.redact("https://ntfy.sh/backup_8fd2/json"),
+            "https://ntfy.sh/backup_***/json"
+        );
+        assert_eq!(Topic::new("backup").redact("/backup"), "/backup");
+    }
+}
@@ -0,0 +1,260 @@
+use futures_util::future::BoxFuture;
+
+use crate::NtfyError;
+
+/// Sends the HTTP requests of a `Client`, e.g. to replace the network with a mock in tests.
+///
+/// The default transport is the `reqwest::Client` built by the `ClientBuilder`. Custom
+/// transports are set via `ClientBuilder::transport` and used for publishing, polling and SSE
+/// subscriptions, but not by the WebSocket or blocking APIs.
+pub trait Transport: Send + Sync {
+    /// Sends `req` and returns the response, regardless of its status code.
+    fn send(&self, req: reqwest::Request) -> BoxFuture<'_, Result<reqwest::Response, NtfyError>>;
+}
+
+impl Transport for reqwest::Client {
+    fn send(&self, req: reqwest::Request) -> BoxFuture<'_, Result<reqwest::Response, NtfyError>> {
+        Box::pin(async move { Ok(self.execute(req).await?) })
+    }
+}
+
+/// The `Transport` of dry-run clients, answering every request without sending it.
+pub(crate) struct DryRun;
+
+impl Transport for DryRun {
+    fn send(&self, req: reqwest::Request) -> BoxFuture<'_, Result<reqwest::Response, NtfyError>> {
+        // the transport doesn't know the topic to redact the url with, so only the host is logged
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            method = %req.method(),
+            host = req.url().host_str().unwrap_or_default(),
+            headers = ?req.headers(),
+            "dry run, not sending request"
+        );
+
+        let res = dry_run_response(req.url()).map(reqwest::Response::from);
+        Box::pin(async move { res })
+    }
+}
+
+/// Builds the synthetic response of a dry run, a published message for the topic of `url`.
+pub(crate) fn dry_run_response(url: &reqwest::Url) -> Result<http::Response<String>, NtfyError> {
+    let topic = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or_default();
+    let body = serde_json::json!({
+        "id": "dry-run",
+        "time": chrono::Utc::now().timestamp(),
+        "event": "message",
+        "topic": topic,
+    });
+
+    http::Response::builder()
+        .status(200)
+        .body(body.to_string())
+        .map_err(|e| NtfyError::InvalidInput(format!("invalid dry run response: {e}")))
+}
+
+#[cfg(feature = "test-util")]
+pub use mock::{MockTransport, RecordedRequest};
+
+#[cfg(feature = "test-util")]
+mod mock {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::future::BoxFuture;
+
+    use super::Transport;
+    use crate::NtfyError;
+
+    /// A request captured by a `MockTransport`.
+    #[derive(Debug, Clone)]
+    pub struct RecordedRequest {
+        /// The HTTP method, e.g. `POST`.
+        pub method: reqwest::Method,
+
+        /// The full URL including query parameters.
+        pub url: reqwest::Url,
+
+        /// All headers of the request.
+        pub headers: reqwest::header::HeaderMap,
+
+        /// The body of the request, empty for streamed bodies.
+        pub body: Vec<u8>,
+    }
+
+    /// A `Transport` that records all requests instead of sending them and answers every request
+    /// with the same response.
+    ///
+    /// Clones share the recorded requests, so a clone can be passed to the `ClientBuilder` while
+    /// the original is used for assertions.
+    #[derive(Debug, Clone)]
+    pub struct MockTransport {
+        /// The status code of every response.
+        status: u16,
+
+        /// The body of every response.
+        body: String,
+
+        /// The requests received so far.
+        requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    }
+
+    impl Default for MockTransport {
+        fn default() -> Self {
+            Self::new(
+                200,
+                r#"{"id":"mock","time":0,"event":"message","topic":"mock"}"#,
+            )
+        }
+    }
+
+    impl MockTransport {
+        /// Constructs a new `MockTransport` answering with `status` and `body`.
+        pub fn new(status: u16, body: &str) -> Self {
+            Self {
+                status,
+                body: body.to_string(),
+                requests: Arc::default(),
+            }
+        }
+
+        /// Returns all requests received so far.
+        pub fn requests(&self) -> Vec<RecordedRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send(
+            &self,
+            req: reqwest::Request,
+        ) -> BoxFuture<'_, Result<reqwest::Response, NtfyError>> {
+            let body = req
+                .body()
+                .and_then(reqwest::Body::as_bytes)
+                .map(<[u8]>::to_vec)
+                .unwrap_or_default();
+            self.requests.lock().unwrap().push(RecordedRequest {
+                method: req.method().clone(),
+                url: req.url().clone(),
+                headers: req.headers().clone(),
+                body,
+            });
+
+            let res = http::Response::builder()
+                .status(self.status)
+                .body(self.body.clone())
+                .map(reqwest::Response::from)
+                .map_err(|e| NtfyError::InvalidInput(format!("invalid mock response: {e}")));
+            Box::pin(async move { res })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{ntfy, Client, Message};
+
+    /// Counts the requests and fails them all.
+    #[derive(Default)]
+    struct Refusing(AtomicUsize);
+
+    impl Transport for Refusing {
+        fn send(&self, _: reqwest::Request) -> BoxFuture<'_, Result<reqwest::Response, NtfyError>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Err(NtfyError::InvalidInput("refused".into())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_requests_through_custom_transports() {
+        let cli = Client::builder()
+            .server("http://127.0.0.1:9")
+            .topic("test")
+            .transport(Refusing::default())
+            .build()
+            .unwrap();
+
+        let res = ntfy(&cli, Message::builder("hi").build().unwrap()).await;
+
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e == "refused"));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn records_requests_in_the_mock_transport() {
+        let mock = MockTransport::default();
+        let cli = Client::builder()
+            .server("https://ntfy.example.com")
+            .topic("backup")
+            .uuid("8fd2")
+            .token("tk_secret")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let msg = Message::builder("done").title("Backup").build().unwrap();
+        let res = ntfy(&cli, msg).await.unwrap();
+
+        assert_eq!(res.id, "mock");
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, reqwest::Method::POST);
+        assert_eq!(
+            requests[0].url.as_str(),
+            "https://ntfy.example.com/backup_8fd2"
+        );
+        assert_eq!(requests[0].headers["Title"], "Backup");
+        assert_eq!(requests[0].headers["Authorization"], "Bearer tk_secret");
+        assert_eq!(requests[0].body, b"done");
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn answers_with_the_configured_response() {
+        let mock = MockTransport::new(403, r#"{"code":40301,"http":403,"error":"forbidden"}"#);
+        let cli = Client::builder()
+            .server("https://ntfy.example.com")
+            .topic("backup")
+            .transport(mock)
+            .build()
+            .unwrap();
+
+        let res = ntfy(&cli, Message::builder("done").build().unwrap()).await;
+
+        assert!(matches!(
+            res,
+            Err(NtfyError::Server {
+                status: 403,
+                error: Some(crate::NtfyServerError { code: 40301, .. }),
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn answers_dry_runs_without_sending() {
+        let server = crate::test_server::TestServer::start(vec![]).await;
+        let cli = Client::builder()
+            .server(&server.url)
+            .topic("backup")
+            .uuid("8fd2")
+            .transport(Refusing::default())
+            .dry_run(true)
+            .build()
+            .unwrap();
+
+        let msg = Message::builder("done").title("Backup").build().unwrap();
+        let res = ntfy(&cli, msg).await.unwrap();
+
+        assert_eq!(res.id, "dry-run");
+        assert_eq!(res.topic, "backup_8fd2");
+        assert_eq!(server.connections(), 0);
+    }
+}
@@ -0,0 +1,305 @@
+use alloc::format;
+use alloc::string::String;
+
+use crate::NtfyError;
+
+/// Loosely checks that `address` looks like an email address, e.g. `phil@example.com`.
+pub(crate) fn email(address: &str) -> Result<(), NtfyError> {
+    if address.contains(',') {
+        return Err(NtfyError::InvalidInput(format!(
+            "ntfy forwards a message to a single email address, got `{address}`, configure an alias on the mail server instead"
+        )));
+    }
+
+    let valid = match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !address.contains(char::is_whitespace)
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(NtfyError::InvalidInput(format!(
+            "`{address}` is not a valid email address"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Rejects plain `http` URLs to hosts other than localhost, which send credentials and messages
+/// unencrypted, unless `allow_insecure` is set.
+#[cfg(feature = "client")]
+pub(crate) fn secure(url: &reqwest::Url, allow_insecure: bool) -> Result<(), NtfyError> {
+    let local = match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    };
+    if url.scheme() == "http" && !local && !allow_insecure {
+        return Err(NtfyError::InvalidInput(format!(
+            "refusing to send to `{}` over plain http, use https or `ClientBuilder::allow_insecure`",
+            url.host_str().unwrap_or_default()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Loosely checks that `number` is either `yes` or a phone number in E.164 form, e.g. `+12223334444`.
+pub(crate) fn phone_number(number: &str) -> Result<(), NtfyError> {
+    if number == "yes" {
+        return Ok(());
+    }
+
+    let digits = number.strip_prefix('+').unwrap_or(number);
+    let valid = (7..=15).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit());
+    if !valid {
+        return Err(NtfyError::InvalidInput(format!(
+            "`{number}` is not a valid phone number, expected e.g. `+12223334444` or `yes`"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks that `url` can be parsed, e.g. `https://example.com`.
+#[cfg(feature = "client")]
+pub(crate) fn url(url: &str) -> Result<(), NtfyError> {
+    reqwest::Url::parse(url)?;
+    Ok(())
+}
+
+/// Loosely checks that `url` starts with a scheme and contains no whitespace, as the URL parser
+/// is only available with the `client` feature.
+#[cfg(not(feature = "client"))]
+pub(crate) fn url(url: &str) -> Result<(), NtfyError> {
+    let valid = url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+    }) && !url.contains(|c: char| c.is_whitespace() || c.is_control());
+    if !valid {
+        return Err(NtfyError::InvalidInput(format!(
+            "`{url}` is not a valid URL"
+        )));
+    }
+
+    Ok(())
+}
+
+/// The maximum number of tags of a message.
+const MAX_TAGS: usize = 32;
+
+/// The maximum length of the comma-separated tag list in bytes.
+const MAX_TAGS_LEN: usize = 1024;
+
+/// Checks that there are at most `MAX_TAGS` tags that fit into `MAX_TAGS_LEN` bytes when joined,
+/// and that no tag contains a comma or control characters.
+pub(crate) fn tags(tags: &[String]) -> Result<(), NtfyError> {
+    if let Some(tag) = tags
+        .iter()
+        .find(|tag| tag.contains(',') || tag.contains(char::is_control))
+    {
+        return Err(NtfyError::InvalidInput(format!(
+            "tag {tag:?} must not contain a comma or control characters"
+        )));
+    }
+    if tags.len() > MAX_TAGS {
+        return Err(NtfyError::InvalidInput(format!(
+            "at most {MAX_TAGS} tags are allowed, got {}",
+            tags.len()
+        )));
+    }
+    let len = tags.iter().map(String::len).sum::<usize>() + tags.len().saturating_sub(1);
+    if len > MAX_TAGS_LEN {
+        return Err(NtfyError::InvalidInput(format!(
+            "tags must fit into {MAX_TAGS_LEN} bytes when joined, got {len}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// The maximum length of a topic, including the uuid suffix.
+#[cfg(feature = "client")]
+const MAX_TOPIC_LEN: usize = 64;
+
+/// Checks that `topic` matches `[-_A-Za-z0-9]{1,64}` as required by ntfy, optionally followed
+/// by the `uuid` suffix.
+///
+/// The error doesn't contain the uuid, which is meant to be secret.
+#[cfg(feature = "client")]
+pub(crate) fn topic(topic: &str, uuid: &str) -> Result<(), NtfyError> {
+    let allowed = |s: &str| {
+        s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    };
+    if topic.is_empty() || topic.len() > MAX_TOPIC_LEN || !allowed(topic) {
+        return Err(NtfyError::InvalidInput(format!(
+            "topic `{topic}` must consist of 1-{MAX_TOPIC_LEN} letters, digits, `-` or `_`"
+        )));
+    }
+    if !uuid.is_empty() && (topic.len() + 1 + uuid.len() > MAX_TOPIC_LEN || !allowed(uuid)) {
+        return Err(NtfyError::InvalidInput(format!(
+            "the uuid must consist of letters, digits, `-` or `_` and fit into {MAX_TOPIC_LEN} characters together with the topic"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks that the API version path segment, e.g. `v1`, is non-empty and consists of letters,
+/// digits, `.`, `-` or `_`.
+#[cfg(feature = "client")]
+pub(crate) fn api_version(version: &str) -> Result<(), NtfyError> {
+    let allowed = version
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'_'));
+    if version.is_empty() || !allowed {
+        return Err(NtfyError::InvalidInput(format!(
+            "api version `{version}` must consist of letters, digits, `.`, `-` or `_`"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_plausible_email_addresses() {
+        assert!(email("phil@example.com").is_ok());
+        assert!(email("first.last+ntfy@mail.example.co.uk").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_email_addresses() {
+        for address in [
+            "",
+            "phil",
+            "@example.com",
+            "phil@example",
+            "phil@@example.com",
+            "phil@.example.com",
+            "phil@example.com.",
+            "phil @example.com",
+        ] {
+            assert!(email(address).is_err(), "{address}");
+        }
+    }
+
+    #[test]
+    fn accepts_phone_numbers_and_yes() {
+        assert!(phone_number("+12223334444").is_ok());
+        assert!(phone_number("4915112345678").is_ok());
+        assert!(phone_number("yes").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_phone_numbers() {
+        for number in ["", "+123", "+1 222 333 4444", "+1222333444455556", "no"] {
+            assert!(phone_number(number).is_err(), "{number}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "client")]
+    fn accepts_valid_topics() {
+        assert!(topic("backup-server_01", "").is_ok());
+        assert!(topic("backup", "8fd2-a1_b").is_ok());
+        assert!(topic(&"a".repeat(64), "").is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "client")]
+    fn rejects_invalid_topics() {
+        for name in ["", "my topic", "a/b", "a,b", "ümlaut", &"a".repeat(65)] {
+            assert!(
+                matches!(topic(name, ""), Err(crate::NtfyError::InvalidInput(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "client")]
+    fn rejects_invalid_uuids_without_leaking_them() {
+        for uuid in ["secret uuid", "secret/uuid", &"s".repeat(60)] {
+            match topic("backup", uuid) {
+                Err(crate::NtfyError::InvalidInput(e)) => assert!(!e.contains(uuid), "{e}"),
+                res => panic!("unexpected result for {uuid}: {res:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_several_email_addresses() {
+        let res = email("a@example.com, b@example.com");
+        assert!(matches!(res, Err(NtfyError::InvalidInput(e)) if e.contains("alias")));
+    }
+
+    #[test]
+    fn checks_urls_with_or_without_the_client() {
+        for valid in [
+            "https://example.com/a?b=c",
+            "intent://scan#Intent;end",
+            "mailto:phil@example.com",
+        ] {
+            assert!(url(valid).is_ok(), "{valid}");
+        }
+        for invalid in [
+            "",
+            "example.com",
+            "https://exa mple.com",
+            "1http://example.com",
+        ] {
+            assert!(url(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn rejects_plain_http_to_public_hosts() {
+        let url = |url: &str| reqwest::Url::parse(url).unwrap();
+
+        assert!(secure(&url("http://ntfy.sh"), false).is_err());
+        assert!(secure(&url("http://10.0.0.5:8080"), false).is_err());
+        assert!(secure(&url("https://ntfy.sh"), false).is_ok());
+        assert!(secure(&url("http://localhost:8080"), false).is_ok());
+        assert!(secure(&url("http://127.0.0.1:8080"), false).is_ok());
+        assert!(secure(&url("http://[::1]:8080"), false).is_ok());
+        assert!(secure(&url("http://ntfy.internal"), true).is_ok());
+    }
+
+    #[test]
+    fn rejects_over_long_tag_lists() {
+        let list = |n: usize, len: usize| vec!["t".repeat(len); n];
+
+        assert!(tags(&list(MAX_TAGS, 1)).is_ok());
+        assert!(matches!(
+            tags(&list(MAX_TAGS + 1, 1)),
+            Err(NtfyError::InvalidInput(e)) if e.contains("at most 32 tags")
+        ));
+        assert!(matches!(
+            tags(&list(2, MAX_TAGS_LEN / 2)),
+            Err(NtfyError::InvalidInput(e)) if e.contains("1024 bytes")
+        ));
+    }
+
+    #[test]
+    fn rejects_tags_with_control_characters() {
+        assert!(tags(&["warning".into(), "skull".into()]).is_ok());
+        for tag in ["two\nlines", "a\tb", "a,b"] {
+            assert!(tags(&[tag.to_string()]).is_err(), "{tag:?}");
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use futures_util::stream::{self, Stream};
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::{http, Message as Frame};
+
+use crate::{authorization, received, Client, NtfyError, ReceivedMessage, SubscribeFilter};
+
+/// Subscribes to the topic of `cli` via [WebSockets](https://docs.ntfy.sh/subscribe/api/#websockets)
+/// and returns a stream of the received messages.
+///
+/// The token or basic auth credentials of `cli` are sent along to read protected topics.
+///
+/// Only messages matching `filter` are delivered. Pings from the server are answered to keep
+/// the connection alive, `open` and `keepalive` events are skipped and the stream ends when the
+/// server closes the connection.
+pub async fn subscribe_ws(
+    cli: &Client,
+    filter: &SubscribeFilter,
+) -> Result<impl Stream<Item = Result<ReceivedMessage, NtfyError>>, NtfyError> {
+    let mut url = filter.url(cli, "/ws")?;
+    let scheme = match url.scheme() {
+        "https" | "wss" => "wss",
+        _ => "ws",
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| NtfyError::InvalidInput(format!("can't use {url} as websocket url")))?;
+
+    let mut req = url.as_str().into_client_request()?;
+    if let Some(value) = authorization(cli)? {
+        let value = http::HeaderValue::from_bytes(value.as_bytes()).map_err(|_| {
+            NtfyError::InvalidInput("credentials contain invalid characters".into())
+        })?;
+        req.headers_mut().insert(http::header::AUTHORIZATION, value);
+    }
+
+    let (socket, _) = tokio_tungstenite::connect_async(req)
+        .await
+        .map_err(NtfyError::from)?;
+
+    Ok(stream::unfold(Some(socket), |socket| async move {
+        let mut socket = socket?;
+        loop {
+            let frame = match socket.next().await? {
+                Ok(frame) => frame,
+                Err(e) => return Some((Err(e.into()), None)),
+            };
+            match frame {
+                Frame::Text(text) => {
+                    if let Some(msg) = received::decode(text.as_str()) {
+                        return Some((msg, Some(socket)));
+                    }
+                }
+                Frame::Ping(_) => {
+                    // tungstenite queues the pong reply, flushing sends it right away
+                    if let Err(e) = socket.flush().await {
+                        return Some((Err(e.into()), None));
+                    }
+                }
+                Frame::Close(_) => return None,
+                _ => {}
+            }
+        }
+    }))
+}
@@ -0,0 +1,8 @@
+#![cfg(feature = "client")]
+
+#[test]
+fn notify_macro() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/notify-pass-*.rs");
+    t.compile_fail("tests/ui/notify-fail-*.rs");
+}
@@ -0,0 +1,7 @@
+use ntfy_rs::{notify, Client};
+
+async fn send(cli: &Client) {
+    let _ = notify!(cli, title: "Done").await;
+}
+
+fn main() {}
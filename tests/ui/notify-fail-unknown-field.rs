@@ -0,0 +1,7 @@
+use ntfy_rs::{notify, Client};
+
+async fn send(cli: &Client) {
+    let _ = notify!(cli, "Backup finished", colour: "red").await;
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use ntfy_rs::{notify, Client, NtfyError, Priority, PublishResponse};
+
+async fn send(cli: &Client) -> Result<PublishResponse, NtfyError> {
+    notify!(cli, "Backup finished").await?;
+    notify!(cli, "Backup finished", title: "Done").await?;
+    notify!(cli, title: "Done", "Backup finished", tags: "white_check_mark", priority: high).await?;
+    notify!(cli, "Disk at 80%", priority: Priority::Low, click: "https://example.com",).await
+}
+
+fn main() {
+    let _ = send;
+}
@@ -0,0 +1,11 @@
+use ntfy_rs::{notify, Client, NtfyError, Priority, PublishResponse};
+
+async fn send(cli: Client, body: String) -> Result<PublishResponse, NtfyError> {
+    let priority = Priority::Max;
+    let title = format!("{} finished", "Backup");
+    notify!(cli, body.as_str(), title: &title, priority: priority).await
+}
+
+fn main() {
+    let _ = send;
+}